@@ -0,0 +1,324 @@
+-- CreateEnum
+CREATE TYPE "Priority" AS ENUM ('NONE', 'LOW', 'MEDIUM', 'HIGH', 'URGENT');
+
+-- CreateEnum
+CREATE TYPE "ShareRole" AS ENUM ('VIEWER', 'EDITOR', 'OWNER');
+
+-- CreateEnum
+CREATE TYPE "TaskEventKind" AS ENUM ('CREATE', 'UPDATE', 'COMPLETE', 'DELETE', 'RESTORE');
+
+-- CreateTable
+CREATE TABLE "User" (
+    "uuid" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "email" TEXT NOT NULL,
+    "password" TEXT NOT NULL,
+    "timezone" TEXT NOT NULL DEFAULT 'UTC',
+
+    CONSTRAINT "User_pkey" PRIMARY KEY ("uuid")
+);
+
+-- CreateTable
+CREATE TABLE "Session" (
+    "cuid" TEXT NOT NULL,
+    "familyCuid" TEXT NOT NULL,
+    "refreshTokenHash" TEXT NOT NULL,
+    "userAgent" TEXT,
+    "ipAddress" TEXT,
+    "userUuid" TEXT NOT NULL,
+    "expiresAt" TIMESTAMP(3) NOT NULL,
+    "rotatedAt" TIMESTAMP(3),
+    "revokedAt" TIMESTAMP(3),
+    "lastSeenAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Session_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "PasswordReset" (
+    "cuid" TEXT NOT NULL,
+    "tokenHash" TEXT NOT NULL,
+    "userUuid" TEXT NOT NULL,
+    "expiresAt" TIMESTAMP(3) NOT NULL,
+    "usedAt" TIMESTAMP(3),
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "PasswordReset_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Task" (
+    "cuid" TEXT NOT NULL,
+    "title" TEXT NOT NULL,
+    "description" TEXT NOT NULL,
+    "done" BOOLEAN NOT NULL DEFAULT false,
+    "priority" "Priority" NOT NULL DEFAULT 'NONE',
+    "dueAt" TIMESTAMP(3),
+    "remindAt" TIMESTAMP(3),
+    "rrule" TEXT,
+    "seriesCuid" TEXT,
+    "statusCuid" TEXT,
+    "position" TEXT NOT NULL DEFAULT 'n',
+    "version" INTEGER NOT NULL DEFAULT 1,
+    "deletedAt" TIMESTAMP(3),
+    "userUuid" TEXT NOT NULL,
+    "projectCuid" TEXT,
+    "parentCuid" TEXT,
+    "searchVector" tsvector,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Task_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Status" (
+    "cuid" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "position" INTEGER NOT NULL,
+    "completed" BOOLEAN NOT NULL DEFAULT false,
+    "anyTransition" BOOLEAN NOT NULL DEFAULT true,
+    "userUuid" TEXT NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Status_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Tag" (
+    "cuid" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "color" TEXT NOT NULL,
+    "userUuid" TEXT NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Tag_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Project" (
+    "cuid" TEXT NOT NULL,
+    "name" TEXT NOT NULL,
+    "userUuid" TEXT NOT NULL,
+    "archivedAt" TIMESTAMP(3),
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Project_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Share" (
+    "cuid" TEXT NOT NULL,
+    "role" "ShareRole" NOT NULL,
+    "userUuid" TEXT NOT NULL,
+    "taskCuid" TEXT,
+    "projectCuid" TEXT,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Share_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Comment" (
+    "cuid" TEXT NOT NULL,
+    "body" TEXT NOT NULL,
+    "edited" BOOLEAN NOT NULL DEFAULT false,
+    "taskCuid" TEXT NOT NULL,
+    "authorUuid" TEXT NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Comment_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "Attachment" (
+    "cuid" TEXT NOT NULL,
+    "filename" TEXT NOT NULL,
+    "contentType" TEXT NOT NULL,
+    "size" INTEGER NOT NULL,
+    "storageKey" TEXT NOT NULL,
+    "taskCuid" TEXT NOT NULL,
+    "uploaderUuid" TEXT NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "Attachment_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "ChecklistItem" (
+    "cuid" TEXT NOT NULL,
+    "text" TEXT NOT NULL,
+    "checked" BOOLEAN NOT NULL DEFAULT false,
+    "position" TEXT NOT NULL,
+    "taskCuid" TEXT NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    "updatedAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "ChecklistItem_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "TaskEvent" (
+    "cuid" TEXT NOT NULL,
+    "kind" "TaskEventKind" NOT NULL,
+    "taskCuid" TEXT NOT NULL,
+    "ownerUuid" TEXT,
+    "actorUuid" TEXT NOT NULL,
+    "changes" JSONB NOT NULL,
+    "createdAt" TIMESTAMP(3) NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    CONSTRAINT "TaskEvent_pkey" PRIMARY KEY ("cuid")
+);
+
+-- CreateTable
+CREATE TABLE "_TagToTask" (
+    "A" TEXT NOT NULL,
+    "B" TEXT NOT NULL
+);
+
+-- CreateTable
+CREATE TABLE "_Dependencies" (
+    "A" TEXT NOT NULL,
+    "B" TEXT NOT NULL
+);
+
+-- CreateTable
+CREATE TABLE "_StatusTransitions" (
+    "A" TEXT NOT NULL,
+    "B" TEXT NOT NULL
+);
+
+-- CreateIndex
+CREATE UNIQUE INDEX "User_email_key" ON "User"("email");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Session_refreshTokenHash_key" ON "Session"("refreshTokenHash");
+
+-- CreateIndex
+CREATE INDEX "Session_familyCuid_idx" ON "Session"("familyCuid");
+
+-- CreateIndex
+CREATE INDEX "Session_userUuid_idx" ON "Session"("userUuid");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "PasswordReset_tokenHash_key" ON "PasswordReset"("tokenHash");
+
+-- CreateIndex
+CREATE INDEX "Task_userUuid_position_idx" ON "Task"("userUuid", "position");
+
+-- CreateIndex
+CREATE INDEX "Task_searchVector_idx" ON "Task" USING GIN ("searchVector");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Status_userUuid_name_key" ON "Status"("userUuid", "name");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Tag_userUuid_name_key" ON "Tag"("userUuid", "name");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Share_userUuid_taskCuid_key" ON "Share"("userUuid", "taskCuid");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Share_userUuid_projectCuid_key" ON "Share"("userUuid", "projectCuid");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "Attachment_storageKey_key" ON "Attachment"("storageKey");
+
+-- CreateIndex
+CREATE INDEX "ChecklistItem_taskCuid_position_idx" ON "ChecklistItem"("taskCuid", "position");
+
+-- CreateIndex
+CREATE INDEX "TaskEvent_taskCuid_idx" ON "TaskEvent"("taskCuid");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "_TagToTask_AB_unique" ON "_TagToTask"("A", "B");
+
+-- CreateIndex
+CREATE INDEX "_TagToTask_B_index" ON "_TagToTask"("B");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "_Dependencies_AB_unique" ON "_Dependencies"("A", "B");
+
+-- CreateIndex
+CREATE INDEX "_Dependencies_B_index" ON "_Dependencies"("B");
+
+-- CreateIndex
+CREATE UNIQUE INDEX "_StatusTransitions_AB_unique" ON "_StatusTransitions"("A", "B");
+
+-- CreateIndex
+CREATE INDEX "_StatusTransitions_B_index" ON "_StatusTransitions"("B");
+
+-- AddForeignKey
+ALTER TABLE "Session" ADD CONSTRAINT "Session_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "PasswordReset" ADD CONSTRAINT "PasswordReset_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Task" ADD CONSTRAINT "Task_statusCuid_fkey" FOREIGN KEY ("statusCuid") REFERENCES "Status"("cuid") ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Task" ADD CONSTRAINT "Task_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Task" ADD CONSTRAINT "Task_projectCuid_fkey" FOREIGN KEY ("projectCuid") REFERENCES "Project"("cuid") ON DELETE SET NULL ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Task" ADD CONSTRAINT "Task_parentCuid_fkey" FOREIGN KEY ("parentCuid") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Status" ADD CONSTRAINT "Status_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Tag" ADD CONSTRAINT "Tag_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Project" ADD CONSTRAINT "Project_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Share" ADD CONSTRAINT "Share_userUuid_fkey" FOREIGN KEY ("userUuid") REFERENCES "User"("uuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Share" ADD CONSTRAINT "Share_taskCuid_fkey" FOREIGN KEY ("taskCuid") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Share" ADD CONSTRAINT "Share_projectCuid_fkey" FOREIGN KEY ("projectCuid") REFERENCES "Project"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Comment" ADD CONSTRAINT "Comment_taskCuid_fkey" FOREIGN KEY ("taskCuid") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Comment" ADD CONSTRAINT "Comment_authorUuid_fkey" FOREIGN KEY ("authorUuid") REFERENCES "User"("uuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Attachment" ADD CONSTRAINT "Attachment_taskCuid_fkey" FOREIGN KEY ("taskCuid") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "Attachment" ADD CONSTRAINT "Attachment_uploaderUuid_fkey" FOREIGN KEY ("uploaderUuid") REFERENCES "User"("uuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "ChecklistItem" ADD CONSTRAINT "ChecklistItem_taskCuid_fkey" FOREIGN KEY ("taskCuid") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "TaskEvent" ADD CONSTRAINT "TaskEvent_actorUuid_fkey" FOREIGN KEY ("actorUuid") REFERENCES "User"("uuid") ON DELETE RESTRICT ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_TagToTask" ADD CONSTRAINT "_TagToTask_A_fkey" FOREIGN KEY ("A") REFERENCES "Tag"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_TagToTask" ADD CONSTRAINT "_TagToTask_B_fkey" FOREIGN KEY ("B") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_Dependencies" ADD CONSTRAINT "_Dependencies_A_fkey" FOREIGN KEY ("A") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_Dependencies" ADD CONSTRAINT "_Dependencies_B_fkey" FOREIGN KEY ("B") REFERENCES "Task"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_StatusTransitions" ADD CONSTRAINT "_StatusTransitions_A_fkey" FOREIGN KEY ("A") REFERENCES "Status"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
+
+-- AddForeignKey
+ALTER TABLE "_StatusTransitions" ADD CONSTRAINT "_StatusTransitions_B_fkey" FOREIGN KEY ("B") REFERENCES "Status"("cuid") ON DELETE CASCADE ON UPDATE CASCADE;
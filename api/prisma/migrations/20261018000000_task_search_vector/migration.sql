@@ -0,0 +1,16 @@
+-- Prisma cannot declare generated columns, so the search document is mapped as Unsupported("tsvector") in the schema
+-- and (re)created here as a stored generated column. Safe to run again on a database where it already exists.
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'Task' AND column_name = 'searchVector' AND is_generated = 'ALWAYS'
+    ) THEN
+        ALTER TABLE "Task" DROP COLUMN IF EXISTS "searchVector";
+        ALTER TABLE "Task" ADD COLUMN "searchVector" tsvector GENERATED ALWAYS AS (
+            setweight(to_tsvector('english', "title"), 'A') || setweight(to_tsvector('english', "description"), 'B')
+        ) STORED;
+    END IF;
+
+    CREATE INDEX IF NOT EXISTS "Task_searchVector_idx" ON "Task" USING GIN ("searchVector");
+END $$;
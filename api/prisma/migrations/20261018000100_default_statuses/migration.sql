@@ -0,0 +1,8 @@
+-- Users created before workflow statuses existed get the same defaults new users receive on registration.
+INSERT INTO "Status" ("cuid", "name", "position", "completed", "anyTransition", "userUuid")
+SELECT gen_random_uuid()::text, defaults."name", defaults."position", defaults."completed", true, "User"."uuid"
+FROM "User"
+CROSS JOIN (
+    VALUES ('Todo', 0, false), ('In progress', 1, false), ('Blocked', 2, false), ('Review', 3, false), ('Done', 4, true)
+) AS defaults("name", "position", "completed")
+WHERE NOT EXISTS (SELECT 1 FROM "Status" WHERE "Status"."userUuid" = "User"."uuid");
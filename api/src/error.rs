@@ -11,11 +11,29 @@ pub struct HttpError {
 
 	#[serde(skip)]
 	pub status: http::StatusCode,
+
+	#[serde(skip)]
+	pub challenge: Option<String>,
 }
 
 impl HttpError {
 	pub fn new(status: http::StatusCode, message: &str) -> Self {
-		Self { status, message: json!({ "error": message }) }
+		Self { status, message: json!({ "error": message }), challenge: None }
+	}
+
+	// A 401 for bearer authentication failures as described in RFC 6750, where `reason` tells clients apart whether to
+	// log in again or refresh their token.
+	pub fn bearer_challenge(reason: &str, error: Option<&str>, message: &str) -> Self {
+		let challenge = match error {
+			Some(error) => format!("Bearer realm=\"api\", error=\"{}\", error_description=\"{}\"", error, message),
+			None => "Bearer realm=\"api\"".to_string(),
+		};
+
+		Self {
+			status: http::StatusCode::UNAUTHORIZED,
+			message: json!({ "error": message, "reason": reason }),
+			challenge: Some(challenge),
+		}
 	}
 
 	pub fn bad_request(message: &str) -> Self {
@@ -34,6 +52,18 @@ impl HttpError {
 		Self::new(http::StatusCode::NOT_FOUND, message)
 	}
 
+	pub fn conflict(message: &str) -> Self {
+		Self::new(http::StatusCode::CONFLICT, message)
+	}
+
+	pub fn precondition_failed(message: &str) -> Self {
+		Self::new(http::StatusCode::PRECONDITION_FAILED, message)
+	}
+
+	pub fn payload_too_large(message: &str) -> Self {
+		Self::new(http::StatusCode::PAYLOAD_TOO_LARGE, message)
+	}
+
 	pub fn internal_server_error(message: &str) -> Self {
 		Self::new(http::StatusCode::INTERNAL_SERVER_ERROR, message)
 	}
@@ -51,8 +81,19 @@ impl std::fmt::Display for HttpError {
 
 impl std::error::Error for HttpError {}
 
+impl HttpError {
+	pub fn to_response(&self) -> HttpResponse {
+		let mut response = HttpResponse::build(self.status);
+		if let Some(challenge) = &self.challenge {
+			response.header(http::header::WWW_AUTHENTICATE, challenge.as_str());
+		}
+
+		response.json(&self.message)
+	}
+}
+
 impl web::WebResponseError for HttpError {
 	fn error_response(&self, _: &web::HttpRequest) -> HttpResponse {
-		HttpResponse::build(self.status).json(&self.message)
+		self.to_response()
 	}
 }
@@ -0,0 +1,26 @@
+use crate::{states::app::AppStateType, utils::datetime::db_now_datetime};
+use std::time::Duration;
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub async fn purge_expired(state: AppStateType) {
+	let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let app_state = state.read().await;
+
+		match app_state.repositories.session.purge_expired_before(db_now_datetime()).await {
+			Ok(0) => {}
+			Ok(count) => info!("Purged {} expired session(s)", count),
+			Err(e) => error!("Failed to purge sessions: {}", e),
+		}
+
+		match app_state.repositories.password_reset.purge_expired_before(db_now_datetime()).await {
+			Ok(0) => {}
+			Ok(count) => info!("Purged {} expired password reset(s)", count),
+			Err(e) => error!("Failed to purge password resets: {}", e),
+		}
+	}
+}
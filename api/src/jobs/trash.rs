@@ -0,0 +1,37 @@
+use crate::{states::app::AppStateType, storage, utils::datetime::db_now_datetime};
+use std::time::Duration;
+
+const DEFAULT_RETENTION_DAYS: i64 = 30;
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+pub fn retention_days() -> i64 {
+	std::env::var("TRASH_RETENTION_DAYS").ok().and_then(|days| days.parse().ok()).unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+pub async fn purge_expired(state: AppStateType, retention_days: i64) {
+	let mut interval = tokio::time::interval(PURGE_INTERVAL);
+
+	loop {
+		interval.tick().await;
+
+		let cutoff = db_now_datetime() - chrono::Duration::days(retention_days);
+		let app_state = state.read().await;
+
+		let storage_keys = match app_state.repositories.attachment.find_keys_trashed_before(cutoff).await {
+			Ok(storage_keys) => storage_keys,
+			Err(e) => {
+				error!("Failed to find attachments of trashed tasks: {}", e);
+				continue;
+			}
+		};
+
+		match app_state.repositories.task.purge_trashed_before(cutoff).await {
+			Ok(0) => {}
+			Ok(count) => {
+				storage::delete_all(app_state.storage.as_ref(), storage_keys).await;
+				info!("Purged {} task(s) from trash", count);
+			}
+			Err(e) => error!("Failed to purge trash: {}", e),
+		}
+	}
+}
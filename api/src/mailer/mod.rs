@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+use std::{io, sync::Arc};
+
+pub mod outbox;
+pub mod smtp;
+
+const DEFAULT_OUTBOX_DIR: &str = "./outbox";
+
+#[derive(Debug, Clone)]
+pub struct Mail {
+	pub to: String,
+	pub subject: String,
+	pub body: String,
+}
+
+#[async_trait]
+pub trait Mailer: Send + Sync {
+	async fn send(&self, mail: Mail) -> io::Result<()>;
+}
+
+// Release builds must choose a mailer explicitly, otherwise mails would silently pile up in the outbox.
+pub fn from_env() -> io::Result<Arc<dyn Mailer>> {
+	match std::env::var("MAILER").as_deref() {
+		Err(_) if !cfg!(debug_assertions) => Err(io::Error::new(io::ErrorKind::InvalidInput, "MAILER must be set")),
+		Ok("smtp") => Ok(Arc::new(smtp::SmtpMailer::from_env()?)),
+		Ok("log") => Ok(Arc::new(outbox::OutboxMailer::new(None))),
+		Ok("outbox") | Err(_) => {
+			let dir = std::env::var("OUTBOX_DIR").unwrap_or_else(|_| DEFAULT_OUTBOX_DIR.to_string());
+			Ok(Arc::new(outbox::OutboxMailer::new(Some(dir.into()))))
+		}
+		Ok(mailer) => Err(io::Error::new(io::ErrorKind::InvalidInput, format!("Unknown mailer {}", mailer))),
+	}
+}
@@ -0,0 +1,33 @@
+use super::{Mail, Mailer};
+use async_trait::async_trait;
+use std::{io, path::PathBuf};
+use tokio::fs;
+
+// Keeps mail local for development: every message is logged, and written to `dir` when one is set.
+pub struct OutboxMailer {
+	dir: Option<PathBuf>,
+}
+
+impl OutboxMailer {
+	pub fn new(dir: Option<PathBuf>) -> Self {
+		Self { dir }
+	}
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+	async fn send(&self, mail: Mail) -> io::Result<()> {
+		info!("Mail to {}: {}", mail.to, mail.subject);
+
+		let Some(dir) = &self.dir else {
+			info!("{}", mail.body);
+			return Ok(());
+		};
+
+		let recipient: String = mail.to.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+		let filename = format!("{}-{}.eml", chrono::Utc::now().format("%Y%m%dT%H%M%S%.6f"), recipient);
+
+		fs::create_dir_all(dir).await?;
+		fs::write(dir.join(filename), format!("To: {}\nSubject: {}\n\n{}\n", mail.to, mail.subject, mail.body)).await
+	}
+}
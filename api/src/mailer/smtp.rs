@@ -0,0 +1,46 @@
+use super::{Mail, Mailer};
+use async_trait::async_trait;
+use lettre::{
+	message::Mailbox, transport::smtp::authentication::Credentials, AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::io;
+
+const DEFAULT_PORT: u16 = 587;
+
+pub struct SmtpMailer {
+	from: Mailbox,
+	transport: AsyncSmtpTransport<Tokio1Executor>,
+}
+
+fn env_var(name: &str) -> io::Result<String> {
+	std::env::var(name).map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, format!("{} must be set", name)))
+}
+
+impl SmtpMailer {
+	pub fn from_env() -> io::Result<Self> {
+		let host = env_var("SMTP_HOST")?;
+		let port = std::env::var("SMTP_PORT").ok().and_then(|port| port.parse().ok()).unwrap_or(DEFAULT_PORT);
+		let from = env_var("MAIL_FROM")?.parse::<Mailbox>().map_err(io::Error::other)?;
+
+		let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&host).map_err(io::Error::other)?.port(port);
+		if let (Ok(username), Ok(password)) = (std::env::var("SMTP_USERNAME"), std::env::var("SMTP_PASSWORD")) {
+			transport = transport.credentials(Credentials::new(username, password));
+		}
+
+		Ok(Self { from, transport: transport.build() })
+	}
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+	async fn send(&self, mail: Mail) -> io::Result<()> {
+		let message = Message::builder()
+			.from(self.from.clone())
+			.to(mail.to.parse::<Mailbox>().map_err(io::Error::other)?)
+			.subject(mail.subject)
+			.body(mail.body)
+			.map_err(io::Error::other)?;
+
+		self.transport.send(message).await.map(|_| ()).map_err(io::Error::other)
+	}
+}
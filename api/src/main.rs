@@ -5,10 +5,14 @@ extern crate log;
 #[allow(warnings, unused)]
 mod db;
 mod error;
+mod jobs;
+mod mailer;
 mod middlewares;
+mod policies;
 mod repositories;
 mod routes;
 mod states;
+mod storage;
 mod utils;
 
 use db::*;
@@ -22,18 +26,33 @@ use tokio::sync::RwLock;
 
 use crate::states::app::AppState;
 
+#[cfg(debug_assertions)]
+const SQL_MIGRATIONS: [&str; 2] = [
+	include_str!("../prisma/migrations/20261018000000_task_search_vector/migration.sql"),
+	include_str!("../prisma/migrations/20261018000100_default_statuses/migration.sql"),
+];
+
 #[ntex::main]
 async fn main() -> std::io::Result<()> {
 	dotenvy::dotenv().ok();
 	pretty_env_logger::init();
 	info!("Starting server...");
+	let keys = utils::jwt::KeyRing::from_env()?;
+	let mailer = mailer::from_env()?;
 	let client = PrismaClient::_builder().build().await.unwrap();
 	info!("Connected to database!");
 
 	println!("Running migrations...");
 
 	#[cfg(debug_assertions)]
-	client._db_push().await.unwrap();
+	{
+		client._db_push().await.unwrap();
+
+		// db push only syncs the Prisma schema, so replay the hand-written migrations for what it cannot express.
+		for migration in SQL_MIGRATIONS {
+			client._execute_raw(prisma_client_rust::Raw::new(migration, vec![])).exec().await.unwrap();
+		}
+	}
 
 	println!("Migrating database...");
 
@@ -45,7 +64,13 @@ async fn main() -> std::io::Result<()> {
 	let client = Arc::new(client);
 	let repositories = repositories::Repositories::new(client.clone());
 
-	let state = Arc::new(RwLock::new(AppState::new(client, repositories)));
+	let state = Arc::new(RwLock::new(AppState::new(client, repositories, storage::from_env(), mailer, keys)));
+
+	let retention_days = jobs::trash::retention_days();
+	info!("Purging trashed tasks after {} day(s)", retention_days);
+	ntex::rt::spawn(jobs::trash::purge_expired(state.clone(), retention_days));
+	ntex::rt::spawn(jobs::session::purge_expired(state.clone()));
+
 	info!("Server is running on http://0.0.0.0:3000");
 	web::server(move || {
 		App::new()
@@ -54,15 +79,28 @@ async fn main() -> std::io::Result<()> {
 			.wrap(
 				Cors::new()
 					.allowed_origin("*")
-					.allowed_methods(vec!["GET", "POST", "DELETE"])
+					.allowed_methods(vec!["GET", "POST", "PUT", "PATCH", "DELETE"])
 					.allowed_headers(vec![http::header::ACCEPT])
 					.allowed_header(http::header::CONTENT_TYPE)
+					.allowed_header(http::header::IF_MATCH)
+					.allowed_header(http::header::RANGE)
+					.expose_headers(vec![
+						http::header::ETAG,
+						http::header::ACCEPT_RANGES,
+						http::header::CONTENT_RANGE,
+						http::header::CONTENT_DISPOSITION,
+						http::header::WWW_AUTHENTICATE,
+					])
 					.max_age(3600)
 					.finish(),
 			)
 			.configure(routes::user::init)
+			.configure(routes::well_known::init)
 			.wrap(middlewares::jwt::JWTMiddlewareBuilder::new())
 			.configure(routes::task::init)
+			.configure(routes::tag::init)
+			.configure(routes::project::init)
+			.configure(routes::status::init)
 	})
 	.bind("0.0.0.0:3000")?
 	.run()
@@ -1,9 +1,92 @@
+use crate::error::HttpError;
+use crate::repositories::session;
 use crate::routes::user::Claims;
 use crate::states::app::AppStateType;
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::errors::ErrorKind;
+use ntex::http::{self, HeaderValue, Method};
 use ntex::service::{Middleware, Service, ServiceCtx};
 use ntex::web::{self};
 
+// The only routes that answer without an access token, every other request is rejected before it reaches its route.
+const PUBLIC_ROUTES: [(Method, &str); 7] = [
+	(Method::POST, "/user/register"),
+	(Method::POST, "/user/login"),
+	(Method::POST, "/user/refresh"),
+	(Method::POST, "/user/logout"),
+	(Method::POST, "/user/password/forgot"),
+	(Method::POST, "/user/password/reset"),
+	(Method::GET, "/.well-known/jwks.json"),
+];
+
+fn is_public(method: &Method, path: &str) -> bool {
+	// CORS preflight requests never carry credentials.
+	*method == Method::OPTIONS
+		|| PUBLIC_ROUTES.iter().any(|(public_method, public_path)| public_method == method && *public_path == path)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuthFailure {
+	Missing,
+	Expired,
+	Invalid,
+	Revoked,
+	Unavailable,
+}
+
+impl AuthFailure {
+	pub fn to_error(self) -> HttpError {
+		match self {
+			AuthFailure::Missing => HttpError::bearer_challenge("missing_token", None, "Missing access token"),
+			AuthFailure::Expired => {
+				HttpError::bearer_challenge("expired_token", Some("invalid_token"), "The access token has expired")
+			}
+			AuthFailure::Invalid => {
+				HttpError::bearer_challenge("invalid_token", Some("invalid_token"), "The access token is invalid")
+			}
+			AuthFailure::Revoked => {
+				HttpError::bearer_challenge("revoked_token", Some("invalid_token"), "The session has been revoked")
+			}
+			AuthFailure::Unavailable => HttpError::service_unavailable("Failed to check session"),
+		}
+	}
+}
+
+async fn authenticate(state: &AppStateType, header: &HeaderValue) -> Result<Claims, AuthFailure> {
+	let Some(token) = header.to_str().ok().and_then(|value| value.strip_prefix("Bearer ")) else {
+		return Err(AuthFailure::Invalid);
+	};
+
+	let app_state = state.read().await;
+
+	let claims = match app_state.keys.decode::<Claims>(token.trim()) {
+		Ok(token_data) => token_data.claims,
+		Err(e) if matches!(e.kind(), ErrorKind::ExpiredSignature) => return Err(AuthFailure::Expired),
+		Err(_) => return Err(AuthFailure::Invalid),
+	};
+
+	if claims.get_expiration() <= chrono::Utc::now().timestamp() as usize {
+		return Err(AuthFailure::Expired);
+	}
+
+	let sessions = &app_state.repositories.session;
+	let last_seen_at = match sessions.find_last_seen(claims.get_session_family()).await {
+		Ok(Some(last_seen_at)) => last_seen_at,
+		Ok(None) => return Err(AuthFailure::Revoked),
+		Err(e) => {
+			error!("Failed to check session: {}", e);
+			return Err(AuthFailure::Unavailable);
+		}
+	};
+
+	if session::is_stale(last_seen_at) {
+		if let Err(e) = sessions.touch(claims.get_session_family()).await {
+			error!("Failed to update session: {}", e);
+		}
+	}
+
+	Ok(claims)
+}
+
 pub struct JWTMiddleware<S> {
 	service: S,
 }
@@ -35,22 +118,22 @@ where
 	ntex::forward_poll_ready!(service);
 
 	async fn call(&self, req: web::WebRequest<Err>, ctx: ServiceCtx<'_, Self>) -> Result<Self::Response, Self::Error> {
-		let secret;
-		{
-			let app_state_guard = req.app_state::<AppStateType>().unwrap().read().await;
-			secret = app_state_guard.secret;
-		}
+		let state = req.app_state::<AppStateType>().unwrap().clone();
 
-		if let Some(token) = req.headers().get("Authorization") {
-			let token = token.to_str().unwrap().replace("Bearer ", "");
-			let token_data =
-				decode::<Claims>(&token, &DecodingKey::from_secret(secret.as_ref()), &Validation::new(Algorithm::HS256));
+		let outcome = match req.headers().get(http::header::AUTHORIZATION) {
+			Some(header) => authenticate(&state, header).await,
+			None => Err(AuthFailure::Missing),
+		};
 
-			if let Ok(token_data) = token_data {
-				if token_data.claims.get_expiration() > chrono::Utc::now().timestamp().try_into().unwrap() {
-					req.extensions_mut().insert(token_data.claims);
-				}
+		// Public routes still see a failed authentication, so that OptionalClaims can reject a bad token they were sent.
+		match outcome {
+			Ok(claims) => {
+				req.extensions_mut().insert(claims);
+			}
+			Err(failure) if is_public(req.method(), req.path()) => {
+				req.extensions_mut().insert(failure);
 			}
+			Err(failure) => return Ok(req.into_response(failure.to_error().to_response())),
 		}
 
 		let res = ctx.call(&self.service, req).await?;
@@ -0,0 +1,21 @@
+use crate::db::ShareRole;
+
+pub mod project;
+pub mod task;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Access {
+	View,
+	Edit,
+	Own,
+}
+
+impl From<ShareRole> for Access {
+	fn from(role: ShareRole) -> Self {
+		match role {
+			ShareRole::Viewer => Access::View,
+			ShareRole::Editor => Access::Edit,
+			ShareRole::Owner => Access::Own,
+		}
+	}
+}
@@ -0,0 +1,28 @@
+use super::Access;
+use crate::{error::HttpError, repositories::project::Project, states::app::AppState};
+
+pub async fn access(app_state: &AppState, user_uuid: &str, project: &Project) -> Result<Option<Access>, HttpError> {
+	if project.user_uuid == user_uuid {
+		return Ok(Some(Access::Own));
+	}
+
+	let roles = match app_state.repositories.share.find_roles(user_uuid.to_string(), vec![], Some(project.cuid.clone())).await {
+		Ok(roles) => roles,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find shares")),
+	};
+
+	Ok(roles.into_iter().map(Access::from).max())
+}
+
+pub async fn authorize(
+	app_state: &AppState,
+	user_uuid: &str,
+	project: &Project,
+	required: Access,
+	action: &str,
+) -> Result<Access, HttpError> {
+	match access(app_state, user_uuid, project).await? {
+		Some(access) if access >= required => Ok(access),
+		_ => Err(HttpError::forbidden(&format!("You are not allowed to {} this project", action))),
+	}
+}
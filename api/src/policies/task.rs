@@ -0,0 +1,34 @@
+use super::Access;
+use crate::{error::HttpError, repositories::task::Task, states::app::AppState};
+
+pub async fn access(app_state: &AppState, user_uuid: &str, task: &Task) -> Result<Option<Access>, HttpError> {
+	if task.user_uuid == user_uuid {
+		return Ok(Some(Access::Own));
+	}
+
+	let mut task_cuids = match app_state.repositories.task.find_ancestor_cuids(task.parent_cuid.clone()).await {
+		Ok(task_cuids) => task_cuids,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find parent tasks")),
+	};
+	task_cuids.push(task.cuid.clone());
+
+	let roles = match app_state.repositories.share.find_roles(user_uuid.to_string(), task_cuids, task.project_cuid.clone()).await {
+		Ok(roles) => roles,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find shares")),
+	};
+
+	Ok(roles.into_iter().map(Access::from).max())
+}
+
+pub async fn authorize(
+	app_state: &AppState,
+	user_uuid: &str,
+	task: &Task,
+	required: Access,
+	action: &str,
+) -> Result<Access, HttpError> {
+	match access(app_state, user_uuid, task).await? {
+		Some(access) if access >= required => Ok(access),
+		_ => Err(HttpError::forbidden(&format!("You are not allowed to {} this task", action))),
+	}
+}
@@ -0,0 +1,133 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::db::*;
+use chrono::{DateTime, FixedOffset};
+use prisma_client_rust::{raw, Direction, PrismaValue, QueryError};
+
+pub type Attachment = attachment::Data;
+
+async fn used_bytes(client: &PrismaClient, uploader_uuid: String) -> Result<i64, QueryError> {
+	let attachments = client
+		.attachment()
+		.find_many(vec![attachment::uploader_uuid::equals(uploader_uuid)])
+		.select(attachment::select!({ size }))
+		.exec()
+		.await?;
+
+	Ok(attachments.into_iter().map(|attachment| attachment.size as i64).sum())
+}
+
+pub struct AttachmentRepository {
+	db_client: DatabaseClient,
+}
+
+impl AttachmentRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	// Returns None when the attachment would take the uploader over `quota_bytes`. The uploader row stays locked until the
+	// attachment is created, so concurrent uploads are counted one after another.
+	pub async fn create(
+		&self,
+		task_cuid: String,
+		uploader_uuid: String,
+		filename: String,
+		content_type: String,
+		size: i32,
+		quota_bytes: i64,
+	) -> Result<Option<Attachment>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				client
+					._execute_raw(raw!(
+						r#"SELECT 1 FROM "User" WHERE "uuid" = {} FOR UPDATE"#,
+						PrismaValue::String(uploader_uuid.clone())
+					))
+					.exec()
+					.await?;
+
+				if used_bytes(&client, uploader_uuid.clone()).await? + size as i64 > quota_bytes {
+					return Ok(None);
+				}
+
+				client
+					.attachment()
+					.create(
+						filename,
+						content_type,
+						size,
+						task::cuid::equals(task_cuid),
+						user::uuid::equals(uploader_uuid),
+						vec![],
+					)
+					.exec()
+					.await
+					.map(Some)
+			})
+			.await
+	}
+
+	pub async fn find_by_task(&self, task_cuid: String) -> Result<Vec<Attachment>, QueryError> {
+		self.db_client
+			.get_db()
+			.attachment()
+			.find_many(vec![attachment::task_cuid::equals(task_cuid)])
+			.order_by(attachment::created_at::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Attachment>, QueryError> {
+		self.db_client.get_db().attachment().find_unique(attachment::cuid::equals(cuid)).exec().await
+	}
+
+	pub async fn used_bytes(&self, uploader_uuid: String) -> Result<i64, QueryError> {
+		used_bytes(&self.db_client.get_db(), uploader_uuid).await
+	}
+
+	pub async fn find_keys_in_subtree(&self, cuid: String) -> Result<Vec<String>, QueryError> {
+		let mut task_cuids = vec![cuid.clone()];
+		let mut frontier = vec![cuid];
+
+		while !frontier.is_empty() {
+			let children = self
+				.db_client
+				.get_db()
+				.task()
+				.find_many(vec![task::parent_cuid::in_vec(frontier)])
+				.select(task::select!({ cuid }))
+				.exec()
+				.await?;
+
+			frontier = children.into_iter().map(|child| child.cuid).collect();
+			task_cuids.extend(frontier.iter().cloned());
+		}
+
+		self.find_keys(vec![task::cuid::in_vec(task_cuids)]).await
+	}
+
+	pub async fn find_keys_trashed_before(&self, cutoff: DateTime<FixedOffset>) -> Result<Vec<String>, QueryError> {
+		self.find_keys(vec![task::deleted_at::lt(cutoff)]).await
+	}
+
+	async fn find_keys(&self, task_filter: Vec<task::WhereParam>) -> Result<Vec<String>, QueryError> {
+		let attachments = self
+			.db_client
+			.get_db()
+			.attachment()
+			.find_many(vec![attachment::task::is(task_filter)])
+			.select(attachment::select!({ storage_key }))
+			.exec()
+			.await?;
+
+		Ok(attachments.into_iter().map(|attachment| attachment.storage_key).collect())
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Attachment, QueryError> {
+		self.db_client.get_db().attachment().delete(attachment::cuid::equals(cuid)).exec().await
+	}
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::{
+	db::*,
+	utils::{
+		datetime::db_now_datetime,
+		rank::{rank_between, spread_ranks},
+	},
+};
+use prisma_client_rust::{Direction, QueryError};
+
+pub type ChecklistItem = checklist_item::Data;
+
+#[derive(Debug, Clone, Default)]
+pub struct ChecklistItemChanges {
+	pub text: Option<String>,
+	pub checked: Option<bool>,
+	pub index: Option<usize>,
+}
+
+async fn rebalance_positions(client: &PrismaClient, task_cuid: &str) -> Result<(), QueryError> {
+	let items = client
+		.checklist_item()
+		.find_many(vec![checklist_item::task_cuid::equals(task_cuid.to_string())])
+		.order_by(checklist_item::position::order(Direction::Asc))
+		.order_by(checklist_item::created_at::order(Direction::Asc))
+		.select(checklist_item::select!({ cuid }))
+		.exec()
+		.await?;
+
+	let ranks = spread_ranks(items.len());
+	for (item, rank) in items.into_iter().zip(ranks) {
+		client
+			.checklist_item()
+			.update(checklist_item::cuid::equals(item.cuid), vec![checklist_item::position::set(rank)])
+			.exec()
+			.await?;
+	}
+
+	Ok(())
+}
+
+// The checklist is part of the task, so every change to it moves the task to a new version.
+async fn touch_task(client: &PrismaClient, task_cuid: String) -> Result<(), QueryError> {
+	client
+		.task()
+		.update(task::cuid::equals(task_cuid), vec![task::version::increment(1), task::updated_at::set(db_now_datetime())])
+		.exec()
+		.await?;
+
+	Ok(())
+}
+
+// Returns the position for an item placed at `index` among the other items of the task, appending when no index is
+// given.
+async fn position_at(
+	client: &PrismaClient,
+	task_cuid: &str,
+	cuid: Option<&str>,
+	index: Option<usize>,
+) -> Result<Option<String>, QueryError> {
+	let mut rebalanced = false;
+
+	loop {
+		let items = client
+			.checklist_item()
+			.find_many(vec![checklist_item::task_cuid::equals(task_cuid.to_string())])
+			.order_by(checklist_item::position::order(Direction::Asc))
+			.order_by(checklist_item::created_at::order(Direction::Asc))
+			.select(checklist_item::select!({ cuid position }))
+			.exec()
+			.await?;
+
+		let positions: Vec<String> =
+			items.into_iter().filter(|item| Some(item.cuid.as_str()) != cuid).map(|item| item.position).collect();
+		let index = index.unwrap_or(positions.len()).min(positions.len());
+
+		let prev = index.checked_sub(1).map(|prev| positions[prev].as_str());
+		let next = positions.get(index).map(String::as_str);
+
+		match rank_between(prev, next) {
+			Some(position) => return Ok(Some(position)),
+			None if !rebalanced => {
+				rebalance_positions(client, task_cuid).await?;
+				rebalanced = true;
+			}
+			None => return Ok(None),
+		}
+	}
+}
+
+pub struct ChecklistRepository {
+	db_client: DatabaseClient,
+}
+
+impl ChecklistRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(
+		&self,
+		task_cuid: String,
+		text: String,
+		checked: bool,
+		index: Option<usize>,
+	) -> Result<Option<ChecklistItem>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let Some(position) = position_at(&client, &task_cuid, None, index).await? else {
+					return Ok(None);
+				};
+
+				let item = client
+					.checklist_item()
+					.create(text, position, task::cuid::equals(task_cuid.clone()), vec![checklist_item::checked::set(checked)])
+					.exec()
+					.await?;
+
+				touch_task(&client, task_cuid).await?;
+
+				Ok(Some(item))
+			})
+			.await
+	}
+
+	pub async fn find_by_task(&self, task_cuid: String) -> Result<Vec<ChecklistItem>, QueryError> {
+		self.db_client
+			.get_db()
+			.checklist_item()
+			.find_many(vec![checklist_item::task_cuid::equals(task_cuid)])
+			.order_by(checklist_item::position::order(Direction::Asc))
+			.order_by(checklist_item::created_at::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<ChecklistItem>, QueryError> {
+		self.db_client.get_db().checklist_item().find_unique(checklist_item::cuid::equals(cuid)).exec().await
+	}
+
+	pub async fn update(
+		&self,
+		cuid: String,
+		task_cuid: String,
+		changes: ChecklistItemChanges,
+	) -> Result<Option<ChecklistItem>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let mut params = vec![checklist_item::updated_at::set(db_now_datetime())];
+
+				if let Some(text) = changes.text {
+					params.push(checklist_item::text::set(text));
+				}
+				if let Some(checked) = changes.checked {
+					params.push(checklist_item::checked::set(checked));
+				}
+				if changes.index.is_some() {
+					let Some(position) = position_at(&client, &task_cuid, Some(&cuid), changes.index).await? else {
+						return Ok(None);
+					};
+					params.push(checklist_item::position::set(position));
+				}
+
+				let item = client.checklist_item().update(checklist_item::cuid::equals(cuid), params).exec().await?;
+
+				touch_task(&client, task_cuid).await?;
+
+				Ok(Some(item))
+			})
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<ChecklistItem, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let item = client.checklist_item().delete(checklist_item::cuid::equals(cuid)).exec().await?;
+
+				touch_task(&client, item.task_cuid.clone()).await?;
+
+				Ok(item)
+			})
+			.await
+	}
+}
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::{db::*, utils::datetime::db_now_datetime};
+use prisma_client_rust::{Direction, QueryError};
+
+comment::select!(comment_with_author {
+	cuid
+	body
+	edited
+	task_cuid
+	author_uuid
+	author: select { uuid name email }
+	created_at
+	updated_at
+});
+
+pub type Comment = comment_with_author::Data;
+
+pub struct CommentRepository {
+	db_client: DatabaseClient,
+}
+
+impl CommentRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(&self, task_cuid: String, author_uuid: String, body: String) -> Result<Comment, QueryError> {
+		self.db_client
+			.get_db()
+			.comment()
+			.create(body, task::cuid::equals(task_cuid), user::uuid::equals(author_uuid), vec![])
+			.select(comment_with_author::select())
+			.exec()
+			.await
+	}
+
+	pub async fn find_by_task(&self, task_cuid: String) -> Result<Vec<Comment>, QueryError> {
+		self.db_client
+			.get_db()
+			.comment()
+			.find_many(vec![comment::task_cuid::equals(task_cuid)])
+			.order_by(comment::created_at::order(Direction::Asc))
+			.select(comment_with_author::select())
+			.exec()
+			.await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Comment>, QueryError> {
+		self.db_client
+			.get_db()
+			.comment()
+			.find_unique(comment::cuid::equals(cuid))
+			.select(comment_with_author::select())
+			.exec()
+			.await
+	}
+
+	pub async fn update(&self, cuid: String, body: String) -> Result<Comment, QueryError> {
+		self.db_client
+			.get_db()
+			.comment()
+			.update(
+				comment::cuid::equals(cuid),
+				vec![comment::body::set(body), comment::edited::set(true), comment::updated_at::set(db_now_datetime())],
+			)
+			.select(comment_with_author::select())
+			.exec()
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Comment, QueryError> {
+		self.db_client.get_db().comment().delete(comment::cuid::equals(cuid)).select(comment_with_author::select()).exec().await
+	}
+}
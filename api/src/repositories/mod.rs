@@ -15,16 +15,49 @@ impl DatabaseClient {
 	}
 }
 
-mod task;
-mod user;
+pub mod attachment;
+pub mod checklist;
+pub mod comment;
+pub mod password_reset;
+pub mod project;
+pub mod session;
+pub mod share;
+pub mod status;
+pub mod tag;
+pub mod task;
+pub mod task_event;
+pub mod user;
 
 pub struct Repositories {
 	pub user: user::UserRepository,
 	pub task: task::TaskRepository,
+	pub tag: tag::TagRepository,
+	pub project: project::ProjectRepository,
+	pub task_event: task_event::TaskEventRepository,
+	pub status: status::StatusRepository,
+	pub share: share::ShareRepository,
+	pub comment: comment::CommentRepository,
+	pub attachment: attachment::AttachmentRepository,
+	pub checklist: checklist::ChecklistRepository,
+	pub session: session::SessionRepository,
+	pub password_reset: password_reset::PasswordResetRepository,
 }
 
 impl Repositories {
 	pub fn new(db: Arc<PrismaClient>) -> Self {
-		Self { user: user::UserRepository::new(db.clone()), task: task::TaskRepository::new(db.clone()) }
+		Self {
+			user: user::UserRepository::new(db.clone()),
+			task: task::TaskRepository::new(db.clone()),
+			tag: tag::TagRepository::new(db.clone()),
+			project: project::ProjectRepository::new(db.clone()),
+			task_event: task_event::TaskEventRepository::new(db.clone()),
+			status: status::StatusRepository::new(db.clone()),
+			share: share::ShareRepository::new(db.clone()),
+			comment: comment::CommentRepository::new(db.clone()),
+			attachment: attachment::AttachmentRepository::new(db.clone()),
+			checklist: checklist::ChecklistRepository::new(db.clone()),
+			session: session::SessionRepository::new(db.clone()),
+			password_reset: password_reset::PasswordResetRepository::new(db.clone()),
+		}
 	}
 }
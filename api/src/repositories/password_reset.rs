@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use super::{user::hash_password, DatabaseClient};
+use crate::{db::*, utils::datetime::db_now_datetime};
+use chrono::{DateTime, FixedOffset};
+use prisma_client_rust::QueryError;
+
+pub type PasswordReset = password_reset::Data;
+
+pub struct PasswordResetRepository {
+	db_client: DatabaseClient,
+}
+
+impl PasswordResetRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	// Issuing a new token invalidates the ones the user has not used yet.
+	pub async fn create(
+		&self,
+		user_uuid: String,
+		token_hash: String,
+		expires_at: DateTime<FixedOffset>,
+	) -> Result<PasswordReset, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				client
+					.password_reset()
+					.delete_many(vec![
+						password_reset::user_uuid::equals(user_uuid.clone()),
+						password_reset::used_at::equals(None),
+					])
+					.exec()
+					.await?;
+
+				client.password_reset().create(token_hash, user::uuid::equals(user_uuid), expires_at, vec![]).exec().await
+			})
+			.await
+	}
+
+	// Uses the token to set a new password and signs the user out everywhere. Returns None when the token is unknown,
+	// expired or already used.
+	pub async fn redeem(&self, token_hash: String, password: String) -> Result<Option<String>, QueryError> {
+		let password = hash_password(password)?;
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let now = db_now_datetime();
+
+				let Some(reset) =
+					client.password_reset().find_unique(password_reset::token_hash::equals(token_hash)).exec().await?
+				else {
+					return Ok(None);
+				};
+
+				let redeemed = client
+					.password_reset()
+					.update_many(
+						vec![
+							password_reset::cuid::equals(reset.cuid),
+							password_reset::used_at::equals(None),
+							password_reset::expires_at::gt(now),
+						],
+						vec![password_reset::used_at::set(Some(now))],
+					)
+					.exec()
+					.await?;
+
+				if redeemed == 0 {
+					return Ok(None);
+				}
+
+				client
+					.user()
+					.update(user::uuid::equals(reset.user_uuid.clone()), vec![user::password::set(password)])
+					.exec()
+					.await?;
+
+				client
+					.session()
+					.update_many(
+						vec![session::user_uuid::equals(reset.user_uuid.clone()), session::revoked_at::equals(None)],
+						vec![session::revoked_at::set(Some(now))],
+					)
+					.exec()
+					.await?;
+
+				Ok(Some(reset.user_uuid))
+			})
+			.await
+	}
+
+	pub async fn purge_expired_before(&self, cutoff: DateTime<FixedOffset>) -> Result<i64, QueryError> {
+		self.db_client.get_db().password_reset().delete_many(vec![password_reset::expires_at::lt(cutoff)]).exec().await
+	}
+}
@@ -0,0 +1,62 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::{db::*, utils::datetime::db_now_datetime};
+use prisma_client_rust::{Direction, QueryError};
+
+pub type Project = project::Data;
+
+pub struct ProjectRepository {
+	db_client: DatabaseClient,
+}
+
+impl ProjectRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(&self, name: String, user_uuid: String) -> Result<Project, QueryError> {
+		self.db_client.get_db().project().create(name, user::uuid::equals(user_uuid), vec![]).exec().await
+	}
+
+	pub async fn find_all(&self, user_uuid: String, include_archived: bool) -> Result<Vec<Project>, QueryError> {
+		let mut params = vec![project::user::is(vec![user::uuid::equals(user_uuid)])];
+
+		if !include_archived {
+			params.push(project::archived_at::equals(None));
+		}
+
+		self.db_client.get_db().project().find_many(params).order_by(project::created_at::order(Direction::Asc)).exec().await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Project>, QueryError> {
+		self.db_client.get_db().project().find_unique(project::cuid::equals(cuid)).exec().await
+	}
+
+	pub async fn update(&self, cuid: String, name: String) -> Result<Project, QueryError> {
+		self.db_client
+			.get_db()
+			.project()
+			.update(project::cuid::equals(cuid), vec![project::name::set(name), project::updated_at::set(db_now_datetime())])
+			.exec()
+			.await
+	}
+
+	pub async fn set_archived(&self, cuid: String, archived: bool) -> Result<Project, QueryError> {
+		let now = db_now_datetime();
+
+		self.db_client
+			.get_db()
+			.project()
+			.update(
+				project::cuid::equals(cuid),
+				vec![project::archived_at::set(archived.then_some(now)), project::updated_at::set(now)],
+			)
+			.exec()
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Project, QueryError> {
+		self.db_client.get_db().project().delete(project::cuid::equals(cuid)).exec().await
+	}
+}
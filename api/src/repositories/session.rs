@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::{db::*, utils::datetime::db_now_datetime};
+use chrono::{DateTime, FixedOffset};
+use prisma_client_rust::{Direction, QueryError};
+
+session::select!(session_summary {
+	family_cuid
+	user_agent
+	ip_address
+	expires_at
+	last_seen_at
+	created_at
+});
+
+pub type Session = session::Data;
+pub type SessionSummary = session_summary::Data;
+
+const LAST_SEEN_RESOLUTION_MINUTES: i64 = 1;
+
+fn active_filter() -> Vec<session::WhereParam> {
+	vec![session::rotated_at::equals(None), session::revoked_at::equals(None), session::expires_at::gt(db_now_datetime())]
+}
+
+fn last_seen_threshold() -> DateTime<FixedOffset> {
+	db_now_datetime() - chrono::Duration::minutes(LAST_SEEN_RESOLUTION_MINUTES)
+}
+
+// Activity is only recorded once per resolution, so most authenticated requests do not write to the session at all.
+pub fn is_stale(last_seen_at: DateTime<FixedOffset>) -> bool {
+	last_seen_at < last_seen_threshold()
+}
+
+pub struct SessionRepository {
+	db_client: DatabaseClient,
+}
+
+impl SessionRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(
+		&self,
+		user_uuid: String,
+		refresh_token_hash: String,
+		expires_at: DateTime<FixedOffset>,
+		user_agent: Option<String>,
+		ip_address: Option<String>,
+	) -> Result<Session, QueryError> {
+		self.db_client
+			.get_db()
+			.session()
+			.create(
+				refresh_token_hash,
+				user::uuid::equals(user_uuid),
+				expires_at,
+				vec![session::user_agent::set(user_agent), session::ip_address::set(ip_address)],
+			)
+			.exec()
+			.await
+	}
+
+	pub async fn find_by_token_hash(&self, refresh_token_hash: String) -> Result<Option<Session>, QueryError> {
+		self.db_client.get_db().session().find_unique(session::refresh_token_hash::equals(refresh_token_hash)).exec().await
+	}
+
+	// Marks the session as used and issues its successor in the same family, which keeps the original sign-in time.
+	// Returns None when the session was already rotated or revoked, which means its refresh token has been used twice.
+	pub async fn rotate(
+		&self,
+		session: Session,
+		refresh_token_hash: String,
+		expires_at: DateTime<FixedOffset>,
+	) -> Result<Option<Session>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let rotated = client
+					.session()
+					.update_many(
+						vec![
+							session::cuid::equals(session.cuid),
+							session::rotated_at::equals(None),
+							session::revoked_at::equals(None),
+						],
+						vec![session::rotated_at::set(Some(db_now_datetime()))],
+					)
+					.exec()
+					.await?;
+
+				if rotated == 0 {
+					return Ok(None);
+				}
+
+				client
+					.session()
+					.create(
+						refresh_token_hash,
+						user::uuid::equals(session.user_uuid),
+						expires_at,
+						vec![
+							session::family_cuid::set(session.family_cuid),
+							session::user_agent::set(session.user_agent),
+							session::ip_address::set(session.ip_address),
+							session::created_at::set(session.created_at),
+						],
+					)
+					.exec()
+					.await
+					.map(Some)
+			})
+			.await
+	}
+
+	// Returns None once every session of the family is revoked or expired.
+	pub async fn find_last_seen(&self, family_cuid: String) -> Result<Option<DateTime<FixedOffset>>, QueryError> {
+		let session = self
+			.db_client
+			.get_db()
+			.session()
+			.find_first(vec![
+				session::family_cuid::equals(family_cuid),
+				session::revoked_at::equals(None),
+				session::expires_at::gt(db_now_datetime()),
+			])
+			.order_by(session::last_seen_at::order(Direction::Desc))
+			.select(session::select!({ last_seen_at }))
+			.exec()
+			.await?;
+
+		Ok(session.map(|session| session.last_seen_at))
+	}
+
+	pub async fn find_active(&self, user_uuid: String) -> Result<Vec<SessionSummary>, QueryError> {
+		let mut filter = active_filter();
+		filter.push(session::user_uuid::equals(user_uuid));
+
+		self.db_client
+			.get_db()
+			.session()
+			.find_many(filter)
+			.order_by(session::last_seen_at::order(Direction::Desc))
+			.select(session_summary::select())
+			.exec()
+			.await
+	}
+
+	pub async fn find_active_one(&self, user_uuid: String, family_cuid: String) -> Result<Option<SessionSummary>, QueryError> {
+		let mut filter = active_filter();
+		filter.extend([session::user_uuid::equals(user_uuid), session::family_cuid::equals(family_cuid)]);
+
+		self.db_client.get_db().session().find_first(filter).select(session_summary::select()).exec().await
+	}
+
+	pub async fn touch(&self, family_cuid: String) -> Result<i64, QueryError> {
+		let mut filter = active_filter();
+		filter.extend([session::family_cuid::equals(family_cuid), session::last_seen_at::lt(last_seen_threshold())]);
+
+		self.db_client.get_db().session().update_many(filter, vec![session::last_seen_at::set(db_now_datetime())]).exec().await
+	}
+
+	pub async fn revoke_family(&self, family_cuid: String) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			.session()
+			.update_many(
+				vec![session::family_cuid::equals(family_cuid), session::revoked_at::equals(None)],
+				vec![session::revoked_at::set(Some(db_now_datetime()))],
+			)
+			.exec()
+			.await
+	}
+
+	pub async fn revoke_others(&self, user_uuid: String, family_cuid: String) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			.session()
+			.update_many(
+				vec![
+					session::user_uuid::equals(user_uuid),
+					session::family_cuid::not(family_cuid),
+					session::revoked_at::equals(None),
+				],
+				vec![session::revoked_at::set(Some(db_now_datetime()))],
+			)
+			.exec()
+			.await
+	}
+
+	pub async fn purge_expired_before(&self, cutoff: DateTime<FixedOffset>) -> Result<i64, QueryError> {
+		self.db_client.get_db().session().delete_many(vec![session::expires_at::lt(cutoff)]).exec().await
+	}
+}
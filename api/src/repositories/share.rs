@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::db::*;
+use prisma_client_rust::{operator::or, Direction, QueryError};
+
+pub type Share = share::Data;
+
+share::select!(share_with_user {
+	cuid
+	role
+	task_cuid
+	project_cuid
+	user: select { uuid name email }
+	created_at
+});
+
+pub type ShareWithUser = share_with_user::Data;
+
+pub(super) async fn has_role(
+	client: &PrismaClient,
+	user_uuid: &str,
+	task_cuids: Vec<String>,
+	project_cuid: Option<String>,
+	roles: Vec<ShareRole>,
+) -> Result<bool, QueryError> {
+	let mut targets = vec![share::task_cuid::in_vec(task_cuids)];
+	if let Some(project_cuid) = project_cuid {
+		targets.push(share::project_cuid::equals(Some(project_cuid)));
+	}
+
+	let count = client
+		.share()
+		.count(vec![share::user_uuid::equals(user_uuid.to_string()), share::role::in_vec(roles), or(targets)])
+		.exec()
+		.await?;
+
+	Ok(count > 0)
+}
+
+pub struct ShareRepository {
+	db_client: DatabaseClient,
+}
+
+impl ShareRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Share>, QueryError> {
+		self.db_client.get_db().share().find_unique(share::cuid::equals(cuid)).exec().await
+	}
+
+	pub async fn find_roles(
+		&self,
+		user_uuid: String,
+		task_cuids: Vec<String>,
+		project_cuid: Option<String>,
+	) -> Result<Vec<ShareRole>, QueryError> {
+		let mut targets = vec![share::task_cuid::in_vec(task_cuids)];
+		if let Some(project_cuid) = project_cuid {
+			targets.push(share::project_cuid::equals(Some(project_cuid)));
+		}
+
+		let shares = self
+			.db_client
+			.get_db()
+			.share()
+			.find_many(vec![share::user_uuid::equals(user_uuid), or(targets)])
+			.select(share::select!({ role }))
+			.exec()
+			.await?;
+
+		Ok(shares.into_iter().map(|share| share.role).collect())
+	}
+
+	pub async fn find_by_task(&self, task_cuid: String) -> Result<Vec<ShareWithUser>, QueryError> {
+		self.db_client
+			.get_db()
+			.share()
+			.find_many(vec![share::task_cuid::equals(Some(task_cuid))])
+			.order_by(share::created_at::order(Direction::Asc))
+			.select(share_with_user::select())
+			.exec()
+			.await
+	}
+
+	pub async fn find_by_project(&self, project_cuid: String) -> Result<Vec<ShareWithUser>, QueryError> {
+		self.db_client
+			.get_db()
+			.share()
+			.find_many(vec![share::project_cuid::equals(Some(project_cuid))])
+			.order_by(share::created_at::order(Direction::Asc))
+			.select(share_with_user::select())
+			.exec()
+			.await
+	}
+
+	pub async fn share_task(&self, task_cuid: String, user_uuid: String, role: ShareRole) -> Result<Share, QueryError> {
+		self.upsert(
+			user_uuid,
+			role,
+			share::task_cuid::equals(Some(task_cuid.clone())),
+			share::task::connect(task::cuid::equals(task_cuid)),
+		)
+		.await
+	}
+
+	pub async fn share_project(&self, project_cuid: String, user_uuid: String, role: ShareRole) -> Result<Share, QueryError> {
+		self.upsert(
+			user_uuid,
+			role,
+			share::project_cuid::equals(Some(project_cuid.clone())),
+			share::project::connect(project::cuid::equals(project_cuid)),
+		)
+		.await
+	}
+
+	async fn upsert(
+		&self,
+		user_uuid: String,
+		role: ShareRole,
+		target: share::WhereParam,
+		connect: share::SetParam,
+	) -> Result<Share, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let existing =
+					client.share().find_first(vec![share::user_uuid::equals(user_uuid.clone()), target]).exec().await?;
+
+				match existing {
+					Some(existing) => {
+						client.share().update(share::cuid::equals(existing.cuid), vec![share::role::set(role)]).exec().await
+					}
+					None => client.share().create(role, user::uuid::equals(user_uuid), vec![connect]).exec().await,
+				}
+			})
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Share, QueryError> {
+		self.db_client.get_db().share().delete(share::cuid::equals(cuid)).exec().await
+	}
+}
@@ -0,0 +1,160 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::db::*;
+use prisma_client_rust::{operator::or, Direction, QueryError};
+
+pub type Status = status::Data;
+
+const DEFAULT_STATUSES: [(&str, bool); 5] =
+	[("Todo", false), ("In progress", false), ("Blocked", false), ("Review", false), ("Done", true)];
+
+// A status accepts any move unless its transitions are restricted, in which case only the statuses in `next` are allowed.
+pub(super) async fn transition_allowed(client: &PrismaClient, from_cuid: String, to_cuid: String) -> Result<bool, QueryError> {
+	if from_cuid == to_cuid {
+		return Ok(true);
+	}
+
+	let count = client
+		.status()
+		.count(vec![
+			status::cuid::equals(from_cuid),
+			or(vec![status::any_transition::equals(true), status::next::some(vec![status::cuid::equals(to_cuid)])]),
+		])
+		.exec()
+		.await?;
+
+	Ok(count > 0)
+}
+
+pub(super) async fn create_defaults(client: &PrismaClient, user_uuid: String) -> Result<i64, QueryError> {
+	client
+		.status()
+		.create_many(
+			DEFAULT_STATUSES
+				.iter()
+				.enumerate()
+				.map(|(position, (name, completed))| {
+					status::create_unchecked(
+						name.to_string(),
+						position as i32,
+						user_uuid.clone(),
+						vec![status::completed::set(*completed)],
+					)
+				})
+				.collect(),
+		)
+		.exec()
+		.await
+}
+
+pub struct StatusRepository {
+	db_client: DatabaseClient,
+}
+
+impl StatusRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(
+		&self,
+		user_uuid: String,
+		name: String,
+		position: Option<i32>,
+		completed: bool,
+		transitions: Option<Vec<String>>,
+	) -> Result<Status, QueryError> {
+		let db = self.db_client.get_db();
+
+		let position = match position {
+			Some(position) => position,
+			None => db.status().count(vec![status::user::is(vec![user::uuid::equals(user_uuid.clone())])]).exec().await? as i32,
+		};
+
+		db.status()
+			.create(
+				name,
+				position,
+				user::uuid::equals(user_uuid),
+				vec![
+					status::completed::set(completed),
+					status::any_transition::set(transitions.is_none()),
+					status::next::connect(transitions.unwrap_or_default().into_iter().map(status::cuid::equals).collect()),
+				],
+			)
+			.with(status::next::fetch(vec![]))
+			.exec()
+			.await
+	}
+
+	pub async fn find_all(&self, user_uuid: String) -> Result<Vec<Status>, QueryError> {
+		self.db_client
+			.get_db()
+			.status()
+			.find_many(vec![status::user::is(vec![user::uuid::equals(user_uuid)])])
+			.with(status::next::fetch(vec![]))
+			.order_by(status::position::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Status>, QueryError> {
+		self.db_client.get_db().status().find_unique(status::cuid::equals(cuid)).with(status::next::fetch(vec![])).exec().await
+	}
+
+	pub async fn find_default(&self, user_uuid: String, completed: bool) -> Result<Option<Status>, QueryError> {
+		self.db_client
+			.get_db()
+			.status()
+			.find_first(vec![status::user::is(vec![user::uuid::equals(user_uuid)]), status::completed::equals(completed)])
+			.order_by(status::position::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	pub async fn can_transition(&self, from_cuid: String, to_cuid: String) -> Result<bool, QueryError> {
+		transition_allowed(&self.db_client.get_db(), from_cuid, to_cuid).await
+	}
+
+	pub async fn count_owned(&self, user_uuid: String, cuids: Vec<String>) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			.status()
+			.count(vec![status::user::is(vec![user::uuid::equals(user_uuid)]), status::cuid::in_vec(cuids)])
+			.exec()
+			.await
+	}
+
+	pub async fn update(
+		&self,
+		cuid: String,
+		name: String,
+		position: Option<i32>,
+		completed: bool,
+		transitions: Option<Vec<String>>,
+	) -> Result<Status, QueryError> {
+		let mut params = vec![
+			status::name::set(name),
+			status::completed::set(completed),
+			status::any_transition::set(transitions.is_none()),
+			status::next::set(transitions.unwrap_or_default().into_iter().map(status::cuid::equals).collect()),
+		];
+
+		if let Some(position) = position {
+			params.push(status::position::set(position));
+		}
+
+		self.db_client
+			.get_db()
+			.status()
+			.update(status::cuid::equals(cuid), params)
+			.with(status::next::fetch(vec![]))
+			.exec()
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Status, QueryError> {
+		self.db_client.get_db().status().delete(status::cuid::equals(cuid)).exec().await
+	}
+}
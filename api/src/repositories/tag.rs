@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use super::DatabaseClient;
+use crate::db::*;
+use prisma_client_rust::{Direction, QueryError};
+
+pub type Tag = tag::Data;
+
+pub struct TagRepository {
+	db_client: DatabaseClient,
+}
+
+impl TagRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn create(&self, name: String, color: String, user_uuid: String) -> Result<Tag, QueryError> {
+		self.db_client.get_db().tag().create(name, color, user::uuid::equals(user_uuid), vec![]).exec().await
+	}
+
+	pub async fn find_all(&self, user_uuid: String) -> Result<Vec<Tag>, QueryError> {
+		self.db_client
+			.get_db()
+			.tag()
+			.find_many(vec![tag::user::is(vec![user::uuid::equals(user_uuid)])])
+			.order_by(tag::name::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Tag>, QueryError> {
+		self.db_client.get_db().tag().find_unique(tag::cuid::equals(cuid)).exec().await
+	}
+
+	pub async fn count_owned(&self, user_uuid: String, cuids: Vec<String>) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			.tag()
+			.count(vec![tag::user::is(vec![user::uuid::equals(user_uuid)]), tag::cuid::in_vec(cuids)])
+			.exec()
+			.await
+	}
+
+	pub async fn update(&self, cuid: String, name: String, color: String) -> Result<Tag, QueryError> {
+		self.db_client
+			.get_db()
+			.tag()
+			.update(tag::cuid::equals(cuid), vec![tag::name::set(name), tag::color::set(color)])
+			.exec()
+			.await
+	}
+
+	pub async fn delete(&self, cuid: String) -> Result<Tag, QueryError> {
+		self.db_client.get_db().tag().delete(tag::cuid::equals(cuid)).exec().await
+	}
+}
@@ -1,11 +1,901 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use super::DatabaseClient;
-use crate::{db::*, utils::datetime::db_now_datetime};
-use prisma_client_rust::QueryError;
+use super::{
+	share::has_role,
+	status::transition_allowed,
+	task_event::{record, record_blockers},
+	DatabaseClient,
+};
+use crate::{
+	db::*,
+	utils::{
+		datetime::{db_now_datetime, parse_timezone},
+		rank::{rank_between, spread_ranks},
+		recurrence::next_occurrence,
+	},
+};
+use chrono::{DateTime, FixedOffset};
+use chrono_tz::Tz;
+use prisma_client_rust::{
+	operator::{and, or},
+	raw, Direction, PrismaValue, QueryError, Raw,
+};
+use serde::{Deserialize, Serialize};
 
 pub type Task = task::Data;
 
+#[derive(Debug, Clone)]
+pub struct TaskFields {
+	pub title: String,
+	pub description: String,
+	pub done: bool,
+	pub priority: Priority,
+	pub due_at: Option<DateTime<FixedOffset>>,
+	pub remind_at: Option<DateTime<FixedOffset>>,
+	pub rrule: Option<String>,
+	pub tags: Option<Vec<String>>,
+	pub status_cuid: Option<String>,
+	pub project_cuid: Option<String>,
+	pub parent_cuid: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskChanges {
+	pub title: Option<String>,
+	pub description: Option<String>,
+	pub done: Option<bool>,
+	pub priority: Option<Priority>,
+	pub due_at: Option<Option<DateTime<FixedOffset>>>,
+	pub remind_at: Option<Option<DateTime<FixedOffset>>>,
+	pub rrule: Option<Option<String>>,
+	pub tags: Option<Vec<String>>,
+	pub status_cuid: Option<Option<String>>,
+	pub project_cuid: Option<Option<String>>,
+	pub parent_cuid: Option<Option<String>>,
+}
+
+impl TaskChanges {
+	fn into_params(self) -> Vec<task::SetParam> {
+		let mut params = vec![task::updated_at::set(db_now_datetime())];
+
+		if let Some(title) = self.title {
+			params.push(task::title::set(title));
+		}
+		if let Some(description) = self.description {
+			params.push(task::description::set(description));
+		}
+		if let Some(done) = self.done {
+			params.push(task::done::set(done));
+		}
+		if let Some(priority) = self.priority {
+			params.push(task::priority::set(priority));
+		}
+		if let Some(due_at) = self.due_at {
+			params.push(task::due_at::set(due_at));
+		}
+		if let Some(remind_at) = self.remind_at {
+			params.push(task::remind_at::set(remind_at));
+		}
+		if let Some(rrule) = self.rrule {
+			params.push(task::rrule::set(rrule));
+		}
+		if let Some(tags) = self.tags {
+			params.push(task::tags::set(tags.into_iter().map(tag::cuid::equals).collect()));
+		}
+		if let Some(status_cuid) = self.status_cuid {
+			params.push(match status_cuid {
+				Some(status_cuid) => task::status::connect(status::cuid::equals(status_cuid)),
+				None => task::status::disconnect(),
+			});
+		}
+		if let Some(project_cuid) = self.project_cuid {
+			params.push(match project_cuid {
+				Some(project_cuid) => task::project::connect(project::cuid::equals(project_cuid)),
+				None => task::project::disconnect(),
+			});
+		}
+		if let Some(parent_cuid) = self.parent_cuid {
+			params.push(match parent_cuid {
+				Some(parent_cuid) => task::parent::connect(task::cuid::equals(parent_cuid)),
+				None => task::parent::disconnect(),
+			});
+		}
+
+		params
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TagMode {
+	#[default]
+	And,
+	Or,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct TaskFilter {
+	pub done: Option<bool>,
+	pub priority: Option<Priority>,
+	pub status_cuid: Option<String>,
+	pub project_cuid: Option<String>,
+	pub parent_cuid: Option<String>,
+	pub root_only: bool,
+	pub tags: Vec<String>,
+	pub tag_mode: TagMode,
+	pub title: Option<String>,
+	pub created_after: Option<DateTime<FixedOffset>>,
+	pub created_before: Option<DateTime<FixedOffset>>,
+	pub updated_after: Option<DateTime<FixedOffset>>,
+	pub updated_before: Option<DateTime<FixedOffset>>,
+}
+
+impl TaskFilter {
+	fn into_params(self, user_uuid: String) -> Vec<task::WhereParam> {
+		let mut params = vec![task::user::is(vec![user::uuid::equals(user_uuid)]), task::deleted_at::equals(None)];
+
+		if let Some(done) = self.done {
+			params.push(task::done::equals(done));
+		}
+		if let Some(priority) = self.priority {
+			params.push(task::priority::equals(priority));
+		}
+		if let Some(status_cuid) = self.status_cuid {
+			params.push(task::status_cuid::equals(Some(status_cuid)));
+		}
+		if let Some(project_cuid) = self.project_cuid {
+			params.push(task::project_cuid::equals(Some(project_cuid)));
+		}
+		if let Some(parent_cuid) = self.parent_cuid {
+			params.push(task::parent_cuid::equals(Some(parent_cuid)));
+		} else if self.root_only {
+			params.push(task::parent_cuid::equals(None));
+		}
+		if !self.tags.is_empty() {
+			match self.tag_mode {
+				TagMode::And => {
+					for name in self.tags {
+						params.push(task::tags::some(vec![tag::name::equals(name)]));
+					}
+				}
+				TagMode::Or => params.push(task::tags::some(vec![tag::name::in_vec(self.tags)])),
+			}
+		}
+		if let Some(title) = self.title {
+			params.push(task::title::contains(title));
+			params.push(task::title::mode(QueryMode::Insensitive));
+		}
+		if let Some(created_after) = self.created_after {
+			params.push(task::created_at::gte(created_after));
+		}
+		if let Some(created_before) = self.created_before {
+			params.push(task::created_at::lt(created_before));
+		}
+		if let Some(updated_after) = self.updated_after {
+			params.push(task::updated_at::gte(updated_after));
+		}
+		if let Some(updated_before) = self.updated_before {
+			params.push(task::updated_at::lt(updated_before));
+		}
+
+		params
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskSort {
+	#[default]
+	Position,
+	CreatedAt,
+	UpdatedAt,
+	Title,
+	Priority,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+	#[default]
+	Asc,
+	Desc,
+}
+
+impl From<SortOrder> for Direction {
+	fn from(order: SortOrder) -> Self {
+		match order {
+			SortOrder::Asc => Direction::Asc,
+			SortOrder::Desc => Direction::Desc,
+		}
+	}
+}
+
+impl TaskSort {
+	fn order_by(&self, order: SortOrder) -> Vec<task::OrderByParam> {
+		match self {
+			TaskSort::Position => vec![task::position::order(order.into()), task::created_at::order(order.into())],
+			TaskSort::CreatedAt => vec![task::created_at::order(order.into())],
+			TaskSort::UpdatedAt => vec![task::updated_at::order(order.into())],
+			TaskSort::Title => vec![task::title::order(order.into())],
+			TaskSort::Priority => vec![task::priority::order(order.into()), task::due_at::order(Direction::Asc)],
+		}
+	}
+
+	// The values `order_by` sorts `task` by, in the same order.
+	fn keys(&self, task: &Task) -> Vec<CursorKey> {
+		match self {
+			TaskSort::Position => vec![CursorKey::Position(task.position.clone()), CursorKey::CreatedAt(task.created_at)],
+			TaskSort::CreatedAt => vec![CursorKey::CreatedAt(task.created_at)],
+			TaskSort::UpdatedAt => vec![CursorKey::UpdatedAt(task.updated_at)],
+			TaskSort::Title => vec![CursorKey::Title(task.title.clone())],
+			TaskSort::Priority => vec![CursorKey::Priority(task.priority), CursorKey::DueAt(task.due_at)],
+		}
+	}
+
+	fn directions(&self, order: SortOrder) -> Vec<Direction> {
+		match self {
+			TaskSort::Position => vec![order.into(), order.into()],
+			TaskSort::Priority => vec![order.into(), Direction::Asc],
+			_ => vec![order.into()],
+		}
+	}
+}
+
+const PRIORITIES: [Priority; 5] = [Priority::None, Priority::Low, Priority::Medium, Priority::High, Priority::Urgent];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CursorKey {
+	Position(String),
+	CreatedAt(DateTime<FixedOffset>),
+	UpdatedAt(DateTime<FixedOffset>),
+	Title(String),
+	Priority(Priority),
+	DueAt(Option<DateTime<FixedOffset>>),
+}
+
+impl CursorKey {
+	fn equals(&self) -> task::WhereParam {
+		match self.clone() {
+			CursorKey::Position(position) => task::position::equals(position),
+			CursorKey::CreatedAt(created_at) => task::created_at::equals(created_at),
+			CursorKey::UpdatedAt(updated_at) => task::updated_at::equals(updated_at),
+			CursorKey::Title(title) => task::title::equals(title),
+			CursorKey::Priority(priority) => task::priority::equals(priority),
+			CursorKey::DueAt(due_at) => task::due_at::equals(due_at),
+		}
+	}
+
+	// Matches the values sorted after the key. Postgres sorts missing due dates last in ascending order, so nothing
+	// comes after them there.
+	fn after(&self, direction: Direction) -> Option<task::WhereParam> {
+		let asc = matches!(direction, Direction::Asc);
+
+		Some(match (self.clone(), asc) {
+			(CursorKey::Position(position), true) => task::position::gt(position),
+			(CursorKey::Position(position), false) => task::position::lt(position),
+			(CursorKey::CreatedAt(created_at), true) => task::created_at::gt(created_at),
+			(CursorKey::CreatedAt(created_at), false) => task::created_at::lt(created_at),
+			(CursorKey::UpdatedAt(updated_at), true) => task::updated_at::gt(updated_at),
+			(CursorKey::UpdatedAt(updated_at), false) => task::updated_at::lt(updated_at),
+			(CursorKey::Title(title), true) => task::title::gt(title),
+			(CursorKey::Title(title), false) => task::title::lt(title),
+			(CursorKey::Priority(priority), asc) => {
+				let rank = PRIORITIES.iter().position(|other| *other == priority).unwrap_or_default();
+				let after = if asc { PRIORITIES[rank + 1..].to_vec() } else { PRIORITIES[..rank].to_vec() };
+				task::priority::in_vec(after)
+			}
+			(CursorKey::DueAt(Some(due_at)), true) => or(vec![task::due_at::gt(due_at), task::due_at::equals(None)]),
+			(CursorKey::DueAt(Some(due_at)), false) => task::due_at::lt(due_at),
+			(CursorKey::DueAt(None), true) => return None,
+			(CursorKey::DueAt(None), false) => task::due_at::not(None),
+		})
+	}
+}
+
+// Points after the last task of a page through its sort keys, so the next page does not depend on that task still
+// existing or matching the filters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskCursor {
+	pub sort: TaskSort,
+	pub order: SortOrder,
+	keys: Vec<CursorKey>,
+	cuid: String,
+}
+
+impl TaskCursor {
+	fn new(sort: TaskSort, order: SortOrder, task: &Task) -> Self {
+		Self { sort, order, keys: sort.keys(task), cuid: task.cuid.clone() }
+	}
+
+	// Builds the keyset comparison `(key1, key2, ..., cuid) > (...)` one leading key at a time.
+	fn into_filter(self) -> task::WhereParam {
+		let mut branches = vec![];
+
+		for (index, (key, direction)) in self.keys.iter().zip(self.sort.directions(self.order)).enumerate() {
+			if let Some(after) = key.after(direction) {
+				let mut branch: Vec<task::WhereParam> = self.keys[..index].iter().map(CursorKey::equals).collect();
+				branch.push(after);
+				branches.push(and(branch));
+			}
+		}
+
+		let mut branch: Vec<task::WhereParam> = self.keys.iter().map(CursorKey::equals).collect();
+		branch.push(match self.order {
+			SortOrder::Asc => task::cuid::gt(self.cuid),
+			SortOrder::Desc => task::cuid::lt(self.cuid),
+		});
+		branches.push(and(branch));
+
+		or(branches)
+	}
+}
+
+pub enum TaskUpdate {
+	Updated(Task),
+	NotFound,
+	Modified,
+	Cycle,
+	InvalidTransition,
+}
+
+pub struct TaskPage {
+	pub tasks: Vec<Task>,
+	pub next_cursor: Option<TaskCursor>,
+	pub total: i64,
+	pub checklist: HashMap<String, ChecklistCount>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChecklistCount {
+	task_cuid: String,
+	pub checked: i64,
+	pub total: i64,
+}
+
+fn open_blockers() -> Vec<task::WhereParam> {
+	vec![task::done::equals(false), task::deleted_at::equals(None)]
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchRow {
+	cuid: String,
+	rank: f64,
+	title_snippet: String,
+	description_snippet: String,
+}
+
+// ts_headline wraps matches in these control characters, which are swapped for <mark> tags once the snippet is escaped.
+const HIGHLIGHT_START: char = '\u{2}';
+const HIGHLIGHT_STOP: char = '\u{3}';
+
+fn highlight(snippet: &str) -> String {
+	snippet
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace(HIGHLIGHT_START, "<mark>")
+		.replace(HIGHLIGHT_STOP, "</mark>")
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchHit {
+	pub task: Task,
+	pub rank: f64,
+	pub title_snippet: String,
+	pub description_snippet: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum BulkAction {
+	SetDone(bool),
+	Trash(Vec<String>),
+	MoveToProject(Option<String>),
+	AddTag(String),
+}
+
+impl BulkAction {
+	fn roles(&self) -> Vec<ShareRole> {
+		match self {
+			Self::Trash(_) => vec![ShareRole::Owner],
+			_ => vec![ShareRole::Editor, ShareRole::Owner],
+		}
+	}
+}
+
+#[derive(Debug)]
+pub enum BulkError {
+	NotFound(String),
+	Forbidden(String),
+	OpenSubtasks(String),
+	InvalidTransition(String),
+	Query(QueryError),
+}
+
+impl BulkError {
+	pub fn cuid(&self) -> Option<&str> {
+		match self {
+			Self::NotFound(cuid) | Self::Forbidden(cuid) | Self::OpenSubtasks(cuid) | Self::InvalidTransition(cuid) => Some(cuid),
+			Self::Query(_) => None,
+		}
+	}
+}
+
+impl From<QueryError> for BulkError {
+	fn from(error: QueryError) -> Self {
+		Self::Query(error)
+	}
+}
+
+async fn update_many_recorded(
+	client: &PrismaClient,
+	actor_uuid: &str,
+	filter: Vec<task::WhereParam>,
+	params: Vec<task::SetParam>,
+) -> Result<i64, QueryError> {
+	let before = client.task().find_many(filter).with(task::tags::fetch(vec![])).exec().await?;
+	if before.is_empty() {
+		return Ok(0);
+	}
+
+	let cuids: Vec<String> = before.iter().map(|task| task.cuid.clone()).collect();
+	let count = client.task().update_many(vec![task::cuid::in_vec(cuids.clone())], params).exec().await?;
+	let after = client.task().find_many(vec![task::cuid::in_vec(cuids)]).with(task::tags::fetch(vec![])).exec().await?;
+
+	for after in &after {
+		let before = before.iter().find(|task| task.cuid == after.cuid);
+		record(client, actor_uuid, before, after).await?;
+	}
+
+	Ok(count)
+}
+
+async fn blocker_cuids(client: &PrismaClient, cuid: String) -> Result<Vec<String>, QueryError> {
+	let blockers = client
+		.task()
+		.find_many(vec![task::blocking::some(vec![task::cuid::equals(cuid)])])
+		.select(task::select!({ cuid }))
+		.order_by(task::cuid::order(Direction::Asc))
+		.exec()
+		.await?;
+
+	Ok(blockers.into_iter().map(|blocker| blocker.cuid).collect())
+}
+
+async fn is_ancestor_or_self(client: &PrismaClient, ancestor_cuid: &str, cuid: String) -> Result<bool, QueryError> {
+	let mut current = Some(cuid);
+
+	while let Some(cuid) = current {
+		if cuid == ancestor_cuid {
+			return Ok(true);
+		}
+
+		current = client
+			.task()
+			.find_unique(task::cuid::equals(cuid))
+			.select(task::select!({ parent_cuid }))
+			.exec()
+			.await?
+			.and_then(|task| task.parent_cuid);
+	}
+
+	Ok(false)
+}
+
+async fn default_status_cuid(
+	client: &PrismaClient,
+	user_uuid: &str,
+	completed: bool,
+) -> Result<Option<String>, QueryError> {
+	let status = client
+		.status()
+		.find_first(vec![status::user_uuid::equals(user_uuid.to_string()), status::completed::equals(completed)])
+		.order_by(status::position::order(Direction::Asc))
+		.select(status::select!({ cuid }))
+		.exec()
+		.await?;
+
+	Ok(status.map(|status| status.cuid))
+}
+
+// Tasks of different owners move to the default status of their own owner.
+async fn set_done(client: &PrismaClient, cuids: Vec<String>, done: bool, actor_uuid: &str) -> Result<i64, QueryError> {
+	let tasks = client
+		.task()
+		.find_many(vec![task::cuid::in_vec(cuids), task::done::equals(!done)])
+		.select(task::select!({ cuid user_uuid }))
+		.exec()
+		.await?;
+
+	let mut owners: HashMap<String, Vec<String>> = HashMap::new();
+	for task in tasks {
+		owners.entry(task.user_uuid).or_default().push(task.cuid);
+	}
+
+	let mut count = 0;
+	for (owner_uuid, cuids) in owners {
+		let mut params = vec![task::done::set(done), task::version::increment(1), task::updated_at::set(db_now_datetime())];
+		if let Some(status_cuid) = default_status_cuid(client, &owner_uuid, done).await? {
+			params.push(task::status_cuid::set(Some(status_cuid)));
+		}
+
+		count +=
+			update_many_recorded(client, actor_uuid, vec![task::cuid::in_vec(cuids), task::done::equals(!done)], params).await?;
+	}
+
+	Ok(count)
+}
+
+// Returns whether every task that changes its done flag may move to the default status for `done`.
+async fn can_set_done(client: &PrismaClient, cuids: &[String], done: bool) -> Result<bool, QueryError> {
+	let tasks = client
+		.task()
+		.find_many(vec![task::cuid::in_vec(cuids.to_vec()), task::done::equals(!done)])
+		.select(task::select!({ user_uuid status_cuid }))
+		.exec()
+		.await?;
+
+	for task in tasks {
+		let Some(from_cuid) = task.status_cuid else {
+			continue;
+		};
+
+		if let Some(to_cuid) = default_status_cuid(client, &task.user_uuid, done).await? {
+			if !transition_allowed(client, from_cuid, to_cuid).await? {
+				return Ok(false);
+			}
+		}
+	}
+
+	Ok(true)
+}
+
+async fn ancestor_cuids(client: &PrismaClient, parent_cuid: Option<String>) -> Result<Vec<String>, QueryError> {
+	let mut ancestors = vec![];
+	let mut current = parent_cuid;
+
+	while let Some(cuid) = current {
+		if ancestors.contains(&cuid) {
+			break;
+		}
+
+		let parent = client
+			.task()
+			.find_unique(task::cuid::equals(cuid.clone()))
+			.select(task::select!({ parent_cuid }))
+			.exec()
+			.await?;
+
+		ancestors.push(cuid);
+		current = parent.and_then(|parent| parent.parent_cuid);
+	}
+
+	Ok(ancestors)
+}
+
+// Mirrors the task policy, so that access revoked after the route authorized the request is still honoured.
+async fn has_access(client: &PrismaClient, actor_uuid: &str, task: &Task, roles: Vec<ShareRole>) -> Result<bool, QueryError> {
+	if task.user_uuid == actor_uuid {
+		return Ok(true);
+	}
+
+	let mut task_cuids = ancestor_cuids(client, task.parent_cuid.clone()).await?;
+	task_cuids.push(task.cuid.clone());
+
+	has_role(client, actor_uuid, task_cuids, task.project_cuid.clone(), roles).await
+}
+
+async fn descendant_cuids(client: &PrismaClient, cuid: String) -> Result<Vec<String>, QueryError> {
+	let mut descendants = vec![];
+	let mut frontier = vec![cuid];
+
+	while !frontier.is_empty() {
+		let children = client
+			.task()
+			.find_many(vec![task::parent_cuid::in_vec(frontier), task::deleted_at::equals(None)])
+			.select(task::select!({ cuid }))
+			.exec()
+			.await?;
+
+		frontier = children.into_iter().map(|child| child.cuid).collect();
+		descendants.extend(frontier.iter().cloned());
+	}
+
+	Ok(descendants)
+}
+
+async fn rebalance_positions(client: &PrismaClient, user_uuid: &str) -> Result<(), QueryError> {
+	let tasks = client
+		.task()
+		.find_many(vec![task::user_uuid::equals(user_uuid.to_string()), task::deleted_at::equals(None)])
+		.order_by(task::position::order(Direction::Asc))
+		.order_by(task::created_at::order(Direction::Asc))
+		.select(task::select!({ cuid }))
+		.exec()
+		.await?;
+
+	if tasks.is_empty() {
+		return Ok(());
+	}
+
+	let ranks = spread_ranks(tasks.len());
+	let values = vec!["({}, {})"; tasks.len()].join(", ");
+	let params = tasks
+		.into_iter()
+		.zip(ranks)
+		.flat_map(|(task, rank)| [PrismaValue::String(task.cuid), PrismaValue::String(rank)])
+		.collect();
+
+	client
+		._execute_raw(Raw::new(
+			&format!(
+				r#"UPDATE "Task" SET "position" = ranks."position"
+				FROM (VALUES {}) AS ranks("cuid", "position")
+				WHERE "Task"."cuid" = ranks."cuid""#,
+				values
+			),
+			params,
+		))
+		.exec()
+		.await?;
+
+	Ok(())
+}
+
+async fn checklist_counts(client: &PrismaClient, cuids: Vec<String>) -> Result<HashMap<String, ChecklistCount>, QueryError> {
+	if cuids.is_empty() {
+		return Ok(HashMap::new());
+	}
+
+	let placeholders = vec!["{}"; cuids.len()].join(", ");
+	let counts: Vec<ChecklistCount> = client
+		._query_raw(Raw::new(
+			&format!(
+				r#"SELECT "taskCuid", COUNT(*) FILTER (WHERE "checked")::int AS "checked", COUNT(*)::int AS "total"
+				FROM "ChecklistItem"
+				WHERE "taskCuid" IN ({})
+				GROUP BY "taskCuid""#,
+				placeholders
+			),
+			cuids.into_iter().map(PrismaValue::String).collect(),
+		))
+		.exec()
+		.await?;
+
+	Ok(counts.into_iter().map(|count| (count.task_cuid.clone(), count)).collect())
+}
+
+async fn position_of(client: &PrismaClient, cuid: &str) -> Result<Option<String>, QueryError> {
+	let task = client.task().find_unique(task::cuid::equals(cuid.to_string())).select(task::select!({ position })).exec().await?;
+
+	Ok(task.map(|task| task.position))
+}
+
+async fn neighbour_position(
+	client: &PrismaClient,
+	user_uuid: &str,
+	cuid: &str,
+	position: &str,
+	direction: Direction,
+) -> Result<Option<String>, QueryError> {
+	let mut filter = vec![
+		task::user_uuid::equals(user_uuid.to_string()),
+		task::cuid::not(cuid.to_string()),
+		task::deleted_at::equals(None),
+	];
+	filter.push(match direction {
+		Direction::Asc => task::position::gt(position.to_string()),
+		Direction::Desc => task::position::lt(position.to_string()),
+	});
+
+	let task = client
+		.task()
+		.find_first(filter)
+		.order_by(task::position::order(direction))
+		.select(task::select!({ position }))
+		.exec()
+		.await?;
+
+	Ok(task.map(|task| task.position))
+}
+
+async fn is_shared_position(client: &PrismaClient, user_uuid: &str, position: Option<&str>) -> Result<bool, QueryError> {
+	let Some(position) = position else {
+		return Ok(false);
+	};
+
+	let count = client
+		.task()
+		.count(vec![
+			task::user_uuid::equals(user_uuid.to_string()),
+			task::position::equals(position.to_string()),
+			task::deleted_at::equals(None),
+		])
+		.exec()
+		.await?;
+
+	Ok(count > 1)
+}
+
+async fn append_position(client: &PrismaClient, user_uuid: &str) -> Result<String, QueryError> {
+	let last = || async {
+		let task = client
+			.task()
+			.find_first(vec![task::user_uuid::equals(user_uuid.to_string()), task::deleted_at::equals(None)])
+			.order_by(task::position::order(Direction::Desc))
+			.select(task::select!({ position }))
+			.exec()
+			.await?;
+
+		Ok::<_, QueryError>(task.map(|task| task.position))
+	};
+
+	if let Some(position) = rank_between(last().await?.as_deref(), None) {
+		return Ok(position);
+	}
+
+	rebalance_positions(client, user_uuid).await?;
+
+	Ok(rank_between(last().await?.as_deref(), None).unwrap_or_default())
+}
+
+async fn owner_timezone(client: &PrismaClient, user_uuid: &str) -> Result<Tz, QueryError> {
+	let user =
+		client.user().find_unique(user::uuid::equals(user_uuid.to_string())).select(user::select!({ timezone })).exec().await?;
+
+	Ok(user.and_then(|user| parse_timezone(&user.timezone)).unwrap_or(Tz::UTC))
+}
+
+// Returns None when the series already has a later occurrence, which happens when a task is completed again after
+// being reopened.
+async fn create_next_occurrence(
+	client: &PrismaClient,
+	actor_uuid: &str,
+	task: &Task,
+	due_at: DateTime<FixedOffset>,
+) -> Result<Option<Task>, QueryError> {
+	let series_cuid = task.series_cuid.clone().unwrap_or_else(|| task.cuid.clone());
+
+	let successors = client
+		.task()
+		.count(vec![
+			task::series_cuid::equals(Some(series_cuid.clone())),
+			task::due_at::gt(task.due_at.unwrap_or(due_at)),
+			task::deleted_at::equals(None),
+		])
+		.exec()
+		.await?;
+
+	if successors > 0 {
+		return Ok(None);
+	}
+
+	let remind_at = match (task.due_at, task.remind_at) {
+		(Some(previous_due_at), Some(remind_at)) => Some(due_at - (previous_due_at - remind_at)),
+		_ => None,
+	};
+	let tags = task.tags.as_ref().map(|tags| tags.iter().map(|tag| tag::cuid::equals(tag.cuid.clone())).collect());
+
+	let mut params = vec![
+		task::priority::set(task.priority.clone()),
+		task::due_at::set(Some(due_at)),
+		task::remind_at::set(remind_at),
+		task::rrule::set(task.rrule.clone()),
+		task::series_cuid::set(Some(series_cuid)),
+		task::tags::connect(tags.unwrap_or_default()),
+	];
+
+	if let Some(project_cuid) = &task.project_cuid {
+		params.push(task::project::connect(project::cuid::equals(project_cuid.clone())));
+	}
+	if let Some(parent_cuid) = &task.parent_cuid {
+		params.push(task::parent::connect(task::cuid::equals(parent_cuid.clone())));
+	}
+
+	params.push(task::position::set(append_position(client, &task.user_uuid).await?));
+	if let Some(status_cuid) = default_status_cuid(client, &task.user_uuid, false).await? {
+		params.push(task::status::connect(status::cuid::equals(status_cuid)));
+	}
+
+	let next = client
+		.task()
+		.create(task.title.clone(), task.description.clone(), user::uuid::equals(task.user_uuid.clone()), params)
+		.with(task::tags::fetch(vec![]))
+		.exec()
+		.await?;
+
+	record(client, actor_uuid, None, &next).await?;
+
+	Ok(Some(next))
+}
+
+async fn apply_bulk_action(
+	client: &PrismaClient,
+	actor_uuid: &str,
+	cuid: String,
+	action: BulkAction,
+) -> Result<(), BulkError> {
+	let now = db_now_datetime();
+
+	let Some(before) = client
+		.task()
+		.find_first(vec![task::cuid::equals(cuid.clone()), task::deleted_at::equals(None)])
+		.with(task::tags::fetch(vec![]))
+		.exec()
+		.await?
+	else {
+		return Err(BulkError::NotFound(cuid));
+	};
+
+	if !has_access(client, actor_uuid, &before, action.roles()).await? {
+		return Err(BulkError::Forbidden(cuid));
+	}
+
+	let mut params = match action {
+		BulkAction::SetDone(done) => {
+			let mut params = vec![task::done::set(done)];
+			if done && !before.done {
+				let open_children = client
+					.task()
+					.count(vec![
+						task::parent_cuid::equals(Some(cuid.clone())),
+						task::done::equals(false),
+						task::deleted_at::equals(None),
+					])
+					.exec()
+					.await?;
+				if open_children > 0 {
+					return Err(BulkError::OpenSubtasks(cuid));
+				}
+			}
+			if before.done != done {
+				if let Some(status_cuid) = default_status_cuid(client, &before.user_uuid, done).await? {
+					if let Some(from_cuid) = before.status_cuid.clone() {
+						if !transition_allowed(client, from_cuid, status_cuid.clone()).await? {
+							return Err(BulkError::InvalidTransition(cuid));
+						}
+					}
+					params.push(task::status::connect(status::cuid::equals(status_cuid)));
+				}
+			}
+			params
+		}
+		BulkAction::Trash(descendants) => {
+			update_many_recorded(
+				client,
+				actor_uuid,
+				vec![task::cuid::in_vec(descendants), task::deleted_at::equals(None)],
+				vec![task::deleted_at::set(Some(now)), task::version::increment(1)],
+			)
+			.await?;
+			vec![task::deleted_at::set(Some(now))]
+		}
+		BulkAction::MoveToProject(Some(project_cuid)) => vec![task::project::connect(project::cuid::equals(project_cuid))],
+		BulkAction::MoveToProject(None) => vec![task::project::disconnect()],
+		BulkAction::AddTag(tag_cuid) => vec![task::tags::connect(vec![tag::cuid::equals(tag_cuid)])],
+	};
+	params.extend([task::version::increment(1), task::updated_at::set(now)]);
+
+	let after = client.task().update(task::cuid::equals(cuid), params).with(task::tags::fetch(vec![])).exec().await?;
+
+	record(client, actor_uuid, Some(&before), &after).await?;
+
+	if before.done || !after.done {
+		return Ok(());
+	}
+
+	if let (Some(rrule), Some(due_at)) = (&after.rrule, after.due_at) {
+		let tz = owner_timezone(client, &after.user_uuid).await?;
+		if let Some(next_due_at) = next_occurrence(rrule, due_at, tz) {
+			create_next_occurrence(client, actor_uuid, &after, next_due_at).await?;
+		}
+	}
+
+	Ok(())
+}
+
 pub struct TaskRepository {
 	db_client: DatabaseClient,
 }
@@ -15,36 +905,791 @@ impl TaskRepository {
 		Self { db_client: DatabaseClient::new(db_client) }
 	}
 
-	pub async fn create(&self, title: String, description: String, user_uuid: String) -> Result<Task, QueryError> {
-		self.db_client.get_db().task().create(title, description, user::uuid::equals(user_uuid), vec![]).exec().await
+	pub async fn create(&self, user_uuid: String, fields: TaskFields) -> Result<Task, QueryError> {
+		let mut params = vec![
+			task::done::set(fields.done),
+			task::priority::set(fields.priority),
+			task::due_at::set(fields.due_at),
+			task::remind_at::set(fields.remind_at),
+			task::rrule::set(fields.rrule),
+			task::tags::connect(fields.tags.unwrap_or_default().into_iter().map(tag::cuid::equals).collect()),
+		];
+
+		if let Some(status_cuid) = fields.status_cuid {
+			params.push(task::status::connect(status::cuid::equals(status_cuid)));
+		}
+		if let Some(project_cuid) = fields.project_cuid {
+			params.push(task::project::connect(project::cuid::equals(project_cuid)));
+		}
+		if let Some(parent_cuid) = fields.parent_cuid {
+			params.push(task::parent::connect(task::cuid::equals(parent_cuid)));
+		}
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				params.push(task::position::set(append_position(&client, &user_uuid).await?));
+
+				let task = client
+					.task()
+					.create(fields.title, fields.description, user::uuid::equals(user_uuid.clone()), params)
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+
+				record(&client, &user_uuid, None, &task).await?;
+
+				Ok(task)
+			})
+			.await
+	}
+
+	pub async fn find_all(
+		&self,
+		user_uuid: String,
+		filter: TaskFilter,
+		sort: TaskSort,
+		order: SortOrder,
+		limit: i64,
+		cursor: Option<TaskCursor>,
+	) -> Result<TaskPage, QueryError> {
+		let db = self.db_client.get_db();
+
+		let total = db.task().count(filter.clone().into_params(user_uuid.clone())).exec().await?;
+
+		let mut params = filter.into_params(user_uuid);
+		if let Some(cursor) = cursor {
+			params.push(cursor.into_filter());
+		}
+
+		let mut query =
+			db.task().find_many(params).with(task::tags::fetch(vec![])).with(task::blocked_by::fetch(open_blockers()));
+		for order_by in sort.order_by(order) {
+			query = query.order_by(order_by);
+		}
+		query = query.order_by(task::cuid::order(order.into())).take(limit + 1);
+
+		let mut tasks = query.exec().await?;
+
+		let next_cursor = if tasks.len() as i64 > limit {
+			tasks.truncate(limit as usize);
+			tasks.last().map(|task| TaskCursor::new(sort, order, task))
+		} else {
+			None
+		};
+
+		let checklist = checklist_counts(&db, tasks.iter().map(|task| task.cuid.clone()).collect()).await?;
+
+		Ok(TaskPage { tasks, next_cursor, total, checklist })
 	}
 
-	pub async fn find_all(&self, user_uuid: String) -> Result<Vec<Task>, QueryError> {
-		self.db_client.get_db().task().find_many(vec![task::user::is(vec![user::uuid::equals(user_uuid)])]).exec().await
+	pub async fn search(
+		&self,
+		user_uuid: String,
+		query: String,
+		limit: i64,
+		offset: i64,
+	) -> Result<Vec<SearchHit>, QueryError> {
+		let db = self.db_client.get_db();
+
+		// Escaping before ts_headline would let it match and cut inside entities, so the snippets are escaped afterwards.
+		let rows: Vec<SearchRow> = db
+			._query_raw(raw!(
+				r#"SELECT "cuid",
+					ts_rank("searchVector", query) AS "rank",
+					ts_headline('english', "title", query,
+						'StartSel=' || chr(2) || ', StopSel=' || chr(3) || ', HighlightAll=true') AS "titleSnippet",
+					ts_headline('english', "description", query,
+						'StartSel=' || chr(2) || ', StopSel=' || chr(3) || ', MaxFragments=2') AS "descriptionSnippet"
+				FROM "Task", websearch_to_tsquery('english', {}) AS query
+				WHERE "userUuid" = {} AND "deletedAt" IS NULL AND "searchVector" @@ query
+				ORDER BY "rank" DESC, "createdAt" DESC
+				LIMIT {} OFFSET {}"#,
+				PrismaValue::String(query),
+				PrismaValue::String(user_uuid),
+				PrismaValue::Int(limit),
+				PrismaValue::Int(offset)
+			))
+			.exec()
+			.await?;
+
+		let cuids = rows.iter().map(|row| row.cuid.clone()).collect();
+		let mut tasks = db
+			.task()
+			.find_many(vec![task::cuid::in_vec(cuids)])
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.exec()
+			.await?;
+
+		Ok(rows
+			.into_iter()
+			.filter_map(|row| {
+				let index = tasks.iter().position(|task| task.cuid == row.cuid)?;
+				Some(SearchHit {
+					task: tasks.swap_remove(index),
+					rank: row.rank,
+					title_snippet: highlight(&row.title_snippet),
+					description_snippet: highlight(&row.description_snippet),
+				})
+			})
+			.collect())
 	}
 
-	pub async fn find_one(&self, cuid: String) -> Result<Option<Task>, QueryError> {
-		self.db_client.get_db().task().find_unique(task::cuid::equals(cuid)).exec().await
+	pub async fn find_due(
+		&self,
+		user_uuid: String,
+		from: Option<DateTime<FixedOffset>>,
+		to: DateTime<FixedOffset>,
+		done: Option<bool>,
+	) -> Result<Vec<Task>, QueryError> {
+		let mut params = vec![
+			task::user::is(vec![user::uuid::equals(user_uuid)]),
+			task::deleted_at::equals(None),
+			task::due_at::lt(to),
+		];
+
+		if let Some(from) = from {
+			params.push(task::due_at::gte(from));
+		}
+		if let Some(done) = done {
+			params.push(task::done::equals(done));
+		}
+
+		self.db_client
+			.get_db()
+			.task()
+			.find_many(params)
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.order_by(task::due_at::order(Direction::Asc))
+			.exec()
+			.await
 	}
 
-	pub async fn update(&self, cuid: String, title: String, description: String, done: bool) -> Result<Task, QueryError> {
+	pub async fn find_one(&self, cuid: String) -> Result<Option<Task>, QueryError> {
 		self.db_client
 			.get_db()
 			.task()
-			.update(
-				task::cuid::equals(cuid),
-				vec![
-					task::title::set(title),
-					task::description::set(description),
-					task::done::set(done),
-					task::updated_at::set(db_now_datetime()),
-				],
+			.find_first(vec![task::cuid::equals(cuid), task::deleted_at::equals(None)])
+			.with(task::tags::fetch(vec![]))
+			.with(
+				task::children::fetch(vec![task::deleted_at::equals(None)]).order_by(task::created_at::order(Direction::Asc)),
 			)
+			.with(task::blocked_by::fetch(open_blockers()))
+			.exec()
+			.await
+	}
+
+	pub async fn find_trashed(&self, user_uuid: String) -> Result<Vec<Task>, QueryError> {
+		self.db_client
+			.get_db()
+			.task()
+			.find_many(vec![task::user::is(vec![user::uuid::equals(user_uuid)]), task::deleted_at::not(None)])
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.order_by(task::deleted_at::order(Direction::Desc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_one_trashed(&self, cuid: String) -> Result<Option<Task>, QueryError> {
+		self.db_client
+			.get_db()
+			.task()
+			.find_first(vec![task::cuid::equals(cuid), task::deleted_at::not(None)])
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.exec()
+			.await
+	}
+
+	pub async fn find_shared(&self, user_uuid: String) -> Result<Vec<Task>, QueryError> {
+		let db = self.db_client.get_db();
+
+		// A task share also grants access to the subtasks below it, so those are collected level by level.
+		let shared = db
+			.task()
+			.find_many(vec![task::shares::some(vec![share::user_uuid::equals(user_uuid.clone())])])
+			.select(task::select!({ cuid }))
+			.exec()
+			.await?;
+
+		let mut cuids = vec![];
+		let mut frontier: Vec<String> = shared.into_iter().map(|task| task.cuid).collect();
+
+		while !frontier.is_empty() {
+			cuids.extend(frontier.iter().cloned());
+
+			let children =
+				db.task().find_many(vec![task::parent_cuid::in_vec(frontier)]).select(task::select!({ cuid })).exec().await?;
+
+			frontier = children.into_iter().map(|child| child.cuid).filter(|cuid| !cuids.contains(cuid)).collect();
+		}
+
+		db.task()
+			.find_many(vec![
+				task::deleted_at::equals(None),
+				or(vec![
+					task::cuid::in_vec(cuids),
+					task::project::is(vec![project::shares::some(vec![share::user_uuid::equals(user_uuid.clone())])]),
+				]),
+			])
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.with(task::shares::fetch(vec![share::user_uuid::equals(user_uuid)]))
+			.order_by(task::updated_at::order(Direction::Desc))
+			.exec()
+			.await
+	}
+
+	pub async fn find_ancestor_cuids(&self, parent_cuid: Option<String>) -> Result<Vec<String>, QueryError> {
+		ancestor_cuids(&self.db_client.get_db(), parent_cuid).await
+	}
+
+	pub async fn find_many(&self, cuids: Vec<String>) -> Result<Vec<Task>, QueryError> {
+		self.db_client
+			.get_db()
+			.task()
+			.find_many(vec![task::cuid::in_vec(cuids), task::deleted_at::equals(None)])
+			.with(task::tags::fetch(vec![]))
+			.with(task::children::fetch(vec![task::deleted_at::equals(None)]))
 			.exec()
 			.await
 	}
 
-	pub async fn delete(&self, cuid: String) -> Result<Task, QueryError> {
-		self.db_client.get_db().task().delete(task::cuid::equals(cuid)).exec().await
+	pub async fn count_open_children(&self, cuid: String) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			.task()
+			.count(vec![
+				task::parent_cuid::equals(Some(cuid)),
+				task::done::equals(false),
+				task::deleted_at::equals(None),
+			])
+			.exec()
+			.await
+	}
+
+	pub async fn find_descendant_cuids(&self, cuid: String) -> Result<Vec<String>, QueryError> {
+		descendant_cuids(&self.db_client.get_db(), cuid).await
+	}
+
+	// Returns None without changing anything when one of the tasks cannot move to the default status for `done`.
+	pub async fn set_done_many(&self, cuids: Vec<String>, done: bool, actor_uuid: String) -> Result<Option<i64>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				if !can_set_done(&client, &cuids, done).await? {
+					return Ok(None);
+				}
+
+				set_done(&client, cuids, done, &actor_uuid).await.map(Some)
+			})
+			.await
+	}
+
+	pub async fn is_blocked_by(&self, cuid: &str, blocker_cuid: String) -> Result<bool, QueryError> {
+		let mut visited = vec![];
+		let mut frontier = vec![blocker_cuid];
+
+		while !frontier.is_empty() {
+			if frontier.iter().any(|blocker_cuid| blocker_cuid == cuid) {
+				return Ok(true);
+			}
+			visited.extend(frontier.iter().cloned());
+
+			let tasks = self
+				.db_client
+				.get_db()
+				.task()
+				.find_many(vec![task::blocking::some(vec![task::cuid::in_vec(frontier)])])
+				.select(task::select!({ cuid }))
+				.exec()
+				.await?;
+
+			frontier = tasks.into_iter().map(|task| task.cuid).filter(|cuid| !visited.contains(cuid)).collect();
+		}
+
+		Ok(false)
+	}
+
+	pub async fn count_open_blockers(&self, cuid: String) -> Result<i64, QueryError> {
+		let mut filter = open_blockers();
+		filter.push(task::blocking::some(vec![task::cuid::equals(cuid)]));
+
+		self.db_client.get_db().task().count(filter).exec().await
+	}
+
+	pub async fn add_blocker(&self, cuid: String, blocker_cuid: String, actor_uuid: String) -> Result<Task, QueryError> {
+		self.update_blockers(cuid, task::blocked_by::connect(vec![task::cuid::equals(blocker_cuid)]), actor_uuid).await
+	}
+
+	pub async fn remove_blocker(&self, cuid: String, blocker_cuid: String, actor_uuid: String) -> Result<Task, QueryError> {
+		self.update_blockers(cuid, task::blocked_by::disconnect(vec![task::cuid::equals(blocker_cuid)]), actor_uuid).await
+	}
+
+	async fn update_blockers(&self, cuid: String, blockers: task::SetParam, actor_uuid: String) -> Result<Task, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let before = blocker_cuids(&client, cuid.clone()).await?;
+
+				let task = client
+					.task()
+					.update(
+						task::cuid::equals(cuid.clone()),
+						vec![blockers, task::version::increment(1), task::updated_at::set(db_now_datetime())],
+					)
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+
+				let after = blocker_cuids(&client, cuid.clone()).await?;
+				record_blockers(&client, &actor_uuid, &task, before, after).await?;
+
+				Ok(task)
+			})
+			.await
+	}
+
+	pub async fn sync_status_done(&self, status_cuid: String, done: bool, actor_uuid: String) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				update_many_recorded(
+					&client,
+					&actor_uuid,
+					vec![task::status_cuid::equals(Some(status_cuid)), task::done::equals(!done)],
+					vec![task::done::set(done), task::version::increment(1), task::updated_at::set(db_now_datetime())],
+				)
+				.await
+			})
+			.await
+	}
+
+	pub async fn complete_finished_ancestors(&self, parent_cuid: Option<String>, actor_uuid: String) -> Result<(), QueryError> {
+		let mut current = parent_cuid;
+
+		while let Some(cuid) = current {
+			let Some(parent) = self.find_one(cuid.clone()).await? else {
+				break;
+			};
+
+			if parent.done || self.count_open_children(cuid.clone()).await? > 0 {
+				break;
+			}
+
+			if self.set_done_many(vec![cuid], true, actor_uuid.clone()).await?.is_none() {
+				break;
+			}
+			current = parent.parent_cuid;
+		}
+
+		Ok(())
+	}
+
+	pub async fn update(
+		&self,
+		cuid: String,
+		changes: TaskChanges,
+		expected_version: Option<i32>,
+		complete_descendants: bool,
+		actor_uuid: String,
+	) -> Result<TaskUpdate, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let Some(before) = client
+					.task()
+					.find_first(vec![task::cuid::equals(cuid.clone()), task::deleted_at::equals(None)])
+					.with(task::tags::fetch(vec![]))
+					.exec()
+					.await?
+				else {
+					return Ok(TaskUpdate::NotFound);
+				};
+
+				if let Some(Some(parent_cuid)) = &changes.parent_cuid {
+					if is_ancestor_or_self(&client, &cuid, parent_cuid.clone()).await? {
+						return Ok(TaskUpdate::Cycle);
+					}
+				}
+
+				let descendants = if complete_descendants { descendant_cuids(&client, cuid.clone()).await? } else { vec![] };
+				if !can_set_done(&client, &descendants, true).await? {
+					return Ok(TaskUpdate::InvalidTransition);
+				}
+
+				let mut filter = vec![task::cuid::equals(cuid.clone()), task::deleted_at::equals(None)];
+				if let Some(expected_version) = expected_version {
+					filter.push(task::version::equals(expected_version));
+				}
+
+				// Without an expected version only a concurrent trash or purge can make the lock miss the task.
+				let locked = client.task().update_many(filter, vec![task::version::increment(1)]).exec().await?;
+				if locked == 0 {
+					return Ok(if expected_version.is_some() { TaskUpdate::Modified } else { TaskUpdate::NotFound });
+				}
+
+				if !descendants.is_empty() {
+					set_done(&client, descendants, true, &actor_uuid).await?;
+				}
+
+				let task = client
+					.task()
+					.update(task::cuid::equals(cuid), changes.into_params())
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+
+				record(&client, &actor_uuid, Some(&before), &task).await?;
+
+				Ok(TaskUpdate::Updated(task))
+			})
+			.await
+	}
+
+	pub async fn spawn_next_occurrence(
+		&self,
+		task: &Task,
+		due_at: DateTime<FixedOffset>,
+		actor_uuid: String,
+	) -> Result<Option<Task>, QueryError> {
+		let task = task.clone();
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move { create_next_occurrence(&client, &actor_uuid, &task, due_at).await })
+			.await
+	}
+
+	pub async fn update_open_series(
+		&self,
+		series_cuid: String,
+		title: String,
+		description: String,
+		priority: Priority,
+		rrule: Option<String>,
+		actor_uuid: String,
+	) -> Result<i64, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				update_many_recorded(
+					&client,
+					&actor_uuid,
+					vec![
+						or(vec![task::series_cuid::equals(Some(series_cuid.clone())), task::cuid::equals(series_cuid)]),
+						task::done::equals(false),
+						task::deleted_at::equals(None),
+					],
+					vec![
+						task::title::set(title),
+						task::description::set(description),
+						task::priority::set(priority),
+						task::rrule::set(rrule),
+						task::version::increment(1),
+						task::updated_at::set(db_now_datetime()),
+					],
+				)
+				.await
+			})
+			.await
+	}
+
+	pub async fn move_between(
+		&self,
+		cuid: String,
+		user_uuid: String,
+		before: Option<String>,
+		after: Option<String>,
+	) -> Result<Option<Task>, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let mut rebalanced = false;
+
+				loop {
+					let before_position = match &before {
+						Some(before) => position_of(&client, before).await?,
+						None => None,
+					};
+					let after_position = match &after {
+						Some(after) => position_of(&client, after).await?,
+						None => None,
+					};
+
+					let prev = match (&after_position, &before_position) {
+						(Some(after_position), _) => Some(after_position.clone()),
+						(None, Some(before_position)) => {
+							neighbour_position(&client, &user_uuid, &cuid, before_position, Direction::Desc).await?
+						}
+						(None, None) => None,
+					};
+					let next = match (&before_position, &after_position) {
+						(Some(before_position), _) => Some(before_position.clone()),
+						(None, Some(after_position)) => {
+							neighbour_position(&client, &user_uuid, &cuid, after_position, Direction::Asc).await?
+						}
+						(None, None) => None,
+					};
+
+					let shared = is_shared_position(&client, &user_uuid, before_position.as_deref()).await?
+						|| is_shared_position(&client, &user_uuid, after_position.as_deref()).await?;
+
+					match rank_between(prev.as_deref(), next.as_deref()) {
+						Some(position) if !shared => {
+							return client
+								.task()
+								.update(task::cuid::equals(cuid), vec![task::position::set(position)])
+								.with(task::tags::fetch(vec![]))
+								.with(task::blocked_by::fetch(open_blockers()))
+								.exec()
+								.await
+								.map(Some);
+						}
+						_ if !rebalanced => {
+							rebalance_positions(&client, &user_uuid).await?;
+							rebalanced = true;
+						}
+						_ => return Ok(None),
+					}
+				}
+			})
+			.await
+	}
+
+	pub async fn move_to_project(
+		&self,
+		cuid: String,
+		project_cuid: Option<String>,
+		actor_uuid: String,
+	) -> Result<Task, QueryError> {
+		let project = match project_cuid {
+			Some(project_cuid) => task::project::connect(project::cuid::equals(project_cuid)),
+			None => task::project::disconnect(),
+		};
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let before =
+					client.task().find_unique(task::cuid::equals(cuid.clone())).with(task::tags::fetch(vec![])).exec().await?;
+
+				let task = client
+					.task()
+					.update(
+						task::cuid::equals(cuid),
+						vec![project, task::version::increment(1), task::updated_at::set(db_now_datetime())],
+					)
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+
+				record(&client, &actor_uuid, before.as_ref(), &task).await?;
+
+				Ok(task)
+			})
+			.await
+	}
+
+	pub async fn trash(
+		&self,
+		cuid: String,
+		expected_version: Option<i32>,
+		descendants: Vec<String>,
+		detach_children: bool,
+		actor_uuid: String,
+	) -> Result<Option<Task>, QueryError> {
+		let now = db_now_datetime();
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let Some(before) =
+					client.task().find_unique(task::cuid::equals(cuid.clone())).with(task::tags::fetch(vec![])).exec().await?
+				else {
+					return Ok(None);
+				};
+
+				let mut filter = vec![task::cuid::equals(cuid.clone()), task::deleted_at::equals(None)];
+				if let Some(expected_version) = expected_version {
+					filter.push(task::version::equals(expected_version));
+				}
+
+				let trashed = client
+					.task()
+					.update_many(filter, vec![task::deleted_at::set(Some(now)), task::version::increment(1)])
+					.exec()
+					.await?;
+
+				if trashed == 0 {
+					return Ok(None);
+				}
+
+				if detach_children {
+					update_many_recorded(
+						&client,
+						&actor_uuid,
+						vec![task::parent_cuid::equals(Some(cuid.clone())), task::deleted_at::equals(None)],
+						vec![task::parent_cuid::set(None), task::version::increment(1), task::updated_at::set(db_now_datetime())],
+					)
+					.await?;
+				}
+
+				update_many_recorded(
+					&client,
+					&actor_uuid,
+					vec![task::cuid::in_vec(descendants), task::deleted_at::equals(None)],
+					vec![task::deleted_at::set(Some(now)), task::version::increment(1)],
+				)
+				.await?;
+
+				let task = client
+					.task()
+					.find_unique(task::cuid::equals(cuid))
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+				if let Some(task) = &task {
+					record(&client, &actor_uuid, Some(&before), task).await?;
+				}
+
+				Ok(task)
+			})
+			.await
+	}
+
+	pub async fn restore(&self, task: Task, actor_uuid: String) -> Result<Task, QueryError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				// Subtasks trashed together with the task share its deletedAt, so only those come back with it.
+				let mut cuids = vec![task.cuid.clone()];
+				let mut frontier = vec![task.cuid.clone()];
+
+				while !frontier.is_empty() {
+					let children = client
+						.task()
+						.find_many(vec![task::parent_cuid::in_vec(frontier), task::deleted_at::equals(task.deleted_at)])
+						.select(task::select!({ cuid }))
+						.exec()
+						.await?;
+
+					frontier = children.into_iter().map(|child| child.cuid).collect();
+					cuids.extend(frontier.iter().cloned());
+				}
+
+				update_many_recorded(
+					&client,
+					&actor_uuid,
+					vec![task::cuid::in_vec(cuids.clone()), task::deleted_at::equals(task.deleted_at)],
+					vec![task::deleted_at::set(None), task::version::increment(1), task::updated_at::set(db_now_datetime())],
+				)
+				.await?;
+
+				// Positions kept from before the trash are stale, so restored tasks are appended to the end of the list.
+				for cuid in cuids {
+					let position = append_position(&client, &task.user_uuid).await?;
+					client.task().update(task::cuid::equals(cuid), vec![task::position::set(position)]).exec().await?;
+				}
+
+				let parent_trashed = match &task.parent_cuid {
+					Some(parent_cuid) => client
+						.task()
+						.find_first(vec![task::cuid::equals(parent_cuid.clone()), task::deleted_at::equals(None)])
+						.exec()
+						.await?
+						.is_none(),
+					None => false,
+				};
+
+				let before =
+					client.task().find_unique(task::cuid::equals(task.cuid.clone())).with(task::tags::fetch(vec![])).exec().await?;
+
+				let mut params = vec![task::updated_at::set(db_now_datetime())];
+				if parent_trashed {
+					params.push(task::parent::disconnect());
+				}
+
+				let restored = client
+					.task()
+					.update(task::cuid::equals(task.cuid), params)
+					.with(task::tags::fetch(vec![]))
+					.with(task::blocked_by::fetch(open_blockers()))
+					.exec()
+					.await?;
+
+				record(&client, &actor_uuid, before.as_ref(), &restored).await?;
+
+				Ok(restored)
+			})
+			.await
+	}
+
+	// Applies every item in a single transaction, rolling all of them back when one of them can no longer be applied.
+	pub async fn apply_bulk(&self, items: Vec<(String, BulkAction)>, actor_uuid: String) -> Result<(), BulkError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				for (cuid, action) in items {
+					apply_bulk_action(&client, &actor_uuid, cuid, action).await?;
+				}
+
+				Ok(())
+			})
+			.await
+	}
+
+	// Applies a single item in its own transaction, so a failing item leaves the others untouched.
+	pub async fn apply_bulk_item(&self, cuid: String, action: BulkAction, actor_uuid: String) -> Result<(), BulkError> {
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move { apply_bulk_action(&client, &actor_uuid, cuid, action).await })
+			.await
+	}
+
+	pub async fn purge(&self, cuid: String) -> Result<Task, QueryError> {
+		self.db_client
+			.get_db()
+			.task()
+			.delete(task::cuid::equals(cuid))
+			.with(task::tags::fetch(vec![]))
+			.with(task::blocked_by::fetch(open_blockers()))
+			.exec()
+			.await
+	}
+
+	pub async fn purge_trashed_before(&self, cutoff: DateTime<FixedOffset>) -> Result<i64, QueryError> {
+		self.db_client.get_db().task().delete_many(vec![task::deleted_at::lt(cutoff)]).exec().await
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn highlight_escapes_text_but_not_marks() {
+		assert_eq!(highlight("\u{2}a&b\u{3} <script>"), "<mark>a&amp;b</mark> &lt;script&gt;");
 	}
 }
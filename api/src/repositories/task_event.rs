@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use super::{task::Task, DatabaseClient};
+use crate::db::*;
+use prisma_client_rust::{Direction, QueryError};
+use serde_json::{json, Map, Value};
+
+pub type TaskEvent = task_event::Data;
+
+const UNTRACKED_FIELDS: [&str; 14] = [
+	"user",
+	"status",
+	"project",
+	"parent",
+	"children",
+	"blockedBy",
+	"blocking",
+	"shares",
+	"comments",
+	"attachments",
+	"checklist",
+	"version",
+	"position",
+	"updatedAt",
+];
+
+fn snapshot(task: &Task) -> Map<String, Value> {
+	let Ok(Value::Object(mut fields)) = serde_json::to_value(task) else {
+		return Map::new();
+	};
+
+	fields.retain(|field, _| !UNTRACKED_FIELDS.contains(&field.as_str()));
+	if let Some(Value::Array(tags)) = fields.get_mut("tags") {
+		*tags = tags.iter().filter_map(|tag| tag.get("cuid").cloned()).collect();
+	}
+
+	fields
+}
+
+fn diff(before: Option<&Task>, after: &Task) -> Map<String, Value> {
+	let before = before.map(snapshot).unwrap_or_default();
+
+	snapshot(after)
+		.into_iter()
+		.filter_map(|(field, value)| {
+			let previous = before.get(&field).cloned().unwrap_or(Value::Null);
+			(previous != value).then(|| (field, json!({ "before": previous, "after": value })))
+		})
+		.collect()
+}
+
+fn kind_of(before: Option<&Task>, after: &Task) -> TaskEventKind {
+	match before {
+		None => TaskEventKind::Create,
+		Some(before) if before.deleted_at.is_none() && after.deleted_at.is_some() => TaskEventKind::Delete,
+		Some(before) if before.deleted_at.is_some() && after.deleted_at.is_none() => TaskEventKind::Restore,
+		Some(before) if !before.done && after.done => TaskEventKind::Complete,
+		Some(_) => TaskEventKind::Update,
+	}
+}
+
+pub(super) async fn record(
+	client: &PrismaClient,
+	actor_uuid: &str,
+	before: Option<&Task>,
+	after: &Task,
+) -> Result<(), QueryError> {
+	let changes = diff(before, after);
+	if before.is_some() && changes.is_empty() {
+		return Ok(());
+	}
+
+	client
+		.task_event()
+		.create(
+			kind_of(before, after),
+			after.cuid.clone(),
+			user::uuid::equals(actor_uuid.to_string()),
+			Value::Object(changes),
+			vec![task_event::owner_uuid::set(Some(after.user_uuid.clone()))],
+		)
+		.exec()
+		.await?;
+
+	Ok(())
+}
+
+// Dependencies are not part of the task snapshot, so their changes are recorded from the blocker cuids instead.
+pub(super) async fn record_blockers(
+	client: &PrismaClient,
+	actor_uuid: &str,
+	task: &Task,
+	before: Vec<String>,
+	after: Vec<String>,
+) -> Result<(), QueryError> {
+	if before == after {
+		return Ok(());
+	}
+
+	client
+		.task_event()
+		.create(
+			TaskEventKind::Update,
+			task.cuid.clone(),
+			user::uuid::equals(actor_uuid.to_string()),
+			json!({ "blockedBy": { "before": before, "after": after } }),
+			vec![task_event::owner_uuid::set(Some(task.user_uuid.clone()))],
+		)
+		.exec()
+		.await?;
+
+	Ok(())
+}
+
+pub struct TaskEventRepository {
+	db_client: DatabaseClient,
+}
+
+impl TaskEventRepository {
+	pub fn new(db_client: Arc<PrismaClient>) -> Self {
+		Self { db_client: DatabaseClient::new(db_client) }
+	}
+
+	pub async fn find_by_task(&self, task_cuid: String) -> Result<Vec<TaskEvent>, QueryError> {
+		self.db_client
+			.get_db()
+			.task_event()
+			.find_many(vec![task_event::task_cuid::equals(task_cuid)])
+			.order_by(task_event::created_at::order(Direction::Asc))
+			.exec()
+			.await
+	}
+
+	// The owner is kept on the events, so it is still known after the task itself was purged.
+	pub async fn find_owner(&self, task_cuid: String) -> Result<Option<String>, QueryError> {
+		let event = self
+			.db_client
+			.get_db()
+			.task_event()
+			.find_first(vec![task_event::task_cuid::equals(task_cuid), task_event::owner_uuid::not(None)])
+			.select(task_event::select!({ owner_uuid }))
+			.exec()
+			.await?;
+
+		Ok(event.and_then(|event| event.owner_uuid))
+	}
+}
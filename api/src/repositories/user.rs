@@ -1,16 +1,29 @@
 use std::sync::Arc;
 
-use super::DatabaseClient;
-use crate::db::*;
+use super::{status::create_defaults, DatabaseClient};
+use crate::{db::*, utils::datetime::db_now_datetime};
 use prisma_client_rust::QueryError;
 
 extern crate bcrypt;
-use bcrypt::{hash, verify};
+use bcrypt::{hash, verify, BcryptError};
 
 pub type User = user::Data;
 
 const SALT_ROUNDS: u32 = 4;
 
+// A malformed stored hash is reported like any other failed query, so routes answer it with a 500 instead of panicking.
+fn bcrypt_error(error: BcryptError) -> QueryError {
+	QueryError::Serialize(format!("Failed to hash password: {}", error))
+}
+
+pub fn hash_password(password: String) -> Result<String, QueryError> {
+	hash(password, SALT_ROUNDS).map_err(bcrypt_error)
+}
+
+fn verify_hash(password: String, hash: &str) -> Result<bool, QueryError> {
+	verify(password, hash).map_err(bcrypt_error)
+}
+
 pub struct UserRepository {
 	db_client: DatabaseClient,
 }
@@ -20,10 +33,19 @@ impl UserRepository {
 		Self { db_client: DatabaseClient::new(db_client) }
 	}
 
-	pub async fn create(&self, name: String, email: String, password: String) -> Result<User, QueryError> {
-		let password = hash(password, SALT_ROUNDS).unwrap();
+	pub async fn create(&self, name: String, email: String, password: String, timezone: String) -> Result<User, QueryError> {
+		let password = hash_password(password)?;
 
-		self.db_client.get_db().user().create(name, email, password, vec![]).exec().await
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let user = client.user().create(name, email, password, vec![user::timezone::set(timezone)]).exec().await?;
+				create_defaults(&client, user.uuid.clone()).await?;
+
+				Ok(user)
+			})
+			.await
 	}
 
 	pub async fn find_by_credentials(&self, email: String, password: String) -> Result<Option<User>, QueryError> {
@@ -31,7 +53,7 @@ impl UserRepository {
 
 		match user {
 			Some(user) => {
-				if verify(password, &user.password).unwrap() {
+				if verify_hash(password, &user.password)? {
 					Ok(Some(user))
 				} else {
 					Ok(None)
@@ -41,7 +63,53 @@ impl UserRepository {
 		}
 	}
 
+	pub async fn find_by_email(&self, email: String) -> Result<Option<User>, QueryError> {
+		self.db_client.get_db().user().find_unique(user::email::equals(email)).exec().await
+	}
+
 	pub async fn find_by_uuid(&self, uuid: String) -> Result<Option<User>, QueryError> {
 		self.db_client.get_db().user().find_unique(user::uuid::equals(uuid)).exec().await
 	}
+
+	pub async fn verify_password(&self, uuid: String, password: String) -> Result<bool, QueryError> {
+		let user = self.find_by_uuid(uuid).await?;
+
+		match user {
+			Some(user) => verify_hash(password, &user.password),
+			None => Ok(false),
+		}
+	}
+
+	// Sets the new password and signs the user out of every session except the one in `family_cuid`.
+	pub async fn update_password(&self, uuid: String, password: String, family_cuid: String) -> Result<User, QueryError> {
+		let password = hash_password(password)?;
+
+		self.db_client
+			.get_db()
+			._transaction()
+			.run(|client| async move {
+				let user =
+					client.user().update(user::uuid::equals(uuid.clone()), vec![user::password::set(password)]).exec().await?;
+
+				client
+					.session()
+					.update_many(
+						vec![
+							session::user_uuid::equals(uuid),
+							session::family_cuid::not(family_cuid),
+							session::revoked_at::equals(None),
+						],
+						vec![session::revoked_at::set(Some(db_now_datetime()))],
+					)
+					.exec()
+					.await?;
+
+				Ok(user)
+			})
+			.await
+	}
+
+	pub async fn update_timezone(&self, uuid: String, timezone: String) -> Result<User, QueryError> {
+		self.db_client.get_db().user().update(user::uuid::equals(uuid), vec![user::timezone::set(timezone)]).exec().await
+	}
 }
@@ -0,0 +1,342 @@
+use crate::{
+	error::HttpError,
+	policies::{self, Access},
+	repositories::{attachment::Attachment, task::Task},
+	routes::user::Claims,
+	states::app::{AppState, AppStateType},
+};
+use futures::StreamExt;
+use ntex::{
+	http,
+	web::{self, HttpRequest, HttpResponse},
+};
+use ntex_multipart::Multipart;
+use serde_json::json;
+use std::ops::Range;
+
+const DEFAULT_MAX_UPLOAD_MB: u64 = 25;
+const DEFAULT_QUOTA_MB: u64 = 100;
+
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+	Full,
+	Partial(Range<u64>),
+	Unsatisfiable,
+}
+
+fn megabytes_from_env(name: &str, default: u64) -> u64 {
+	std::env::var(name).ok().and_then(|mb| mb.parse().ok()).unwrap_or(default) * 1024 * 1024
+}
+
+fn max_upload_bytes() -> u64 {
+	megabytes_from_env("ATTACHMENT_MAX_MB", DEFAULT_MAX_UPLOAD_MB)
+}
+
+fn quota_bytes() -> u64 {
+	megabytes_from_env("ATTACHMENT_QUOTA_MB", DEFAULT_QUOTA_MB)
+}
+
+async fn find_task(app_state: &AppState, cuid: String) -> Result<Task, HttpError> {
+	match app_state.repositories.task.find_one(cuid).await {
+		Ok(Some(task)) => Ok(task),
+		Ok(None) => Err(HttpError::not_found("Task not found")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find task")),
+	}
+}
+
+async fn find_attachment(app_state: &AppState, task_cuid: &str, cuid: String) -> Result<Attachment, HttpError> {
+	match app_state.repositories.attachment.find_one(cuid).await {
+		Ok(Some(attachment)) if attachment.task_cuid == task_cuid => Ok(attachment),
+		Ok(_) => Err(HttpError::not_found("Attachment not found")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find attachment")),
+	}
+}
+
+fn sanitize_filename(filename: &str) -> String {
+	let basename = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+	let sanitized: String = basename.chars().filter(|c| !c.is_control() && *c != '"').collect();
+
+	match sanitized.trim() {
+		"" | "." | ".." => "attachment".to_string(),
+		trimmed => trimmed.to_string(),
+	}
+}
+
+fn content_disposition(filename: &str) -> String {
+	let fallback: String = filename.chars().map(|c| if c.is_ascii() { c } else { '_' }).collect();
+	let encoded: String = filename
+		.bytes()
+		.map(|b| match b {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (b as char).to_string(),
+			_ => format!("%{:02X}", b),
+		})
+		.collect();
+
+	format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
+
+fn parse_range(spec: &str, size: u64) -> ByteRange {
+	// Multiple ranges and malformed headers are ignored and the whole file is served.
+	let Some((start, end)) = spec.trim().strip_prefix("bytes=").and_then(|range| range.split_once('-')) else {
+		return ByteRange::Full;
+	};
+
+	let range = match (start.trim(), end.trim()) {
+		("", suffix) => match suffix.parse::<u64>() {
+			Ok(0) => return ByteRange::Unsatisfiable,
+			Ok(suffix) => size.saturating_sub(suffix)..size,
+			Err(_) => return ByteRange::Full,
+		},
+		(start, "") => match start.parse::<u64>() {
+			Ok(start) => start..size,
+			Err(_) => return ByteRange::Full,
+		},
+		(start, end) => match (start.parse::<u64>(), end.parse::<u64>()) {
+			Ok((start, end)) if start <= end => start..end.saturating_add(1).min(size),
+			_ => return ByteRange::Full,
+		},
+	};
+
+	if range.is_empty() || range.start >= size {
+		return ByteRange::Unsatisfiable;
+	}
+
+	ByteRange::Partial(range)
+}
+
+#[web::get("/{cuid}/attachments")]
+pub async fn find_all(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let attachments = match app_state.repositories.attachment.find_by_task(cuid.clone()).await {
+		Ok(attachments) => attachments,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find attachments")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "attachments": attachments })))
+}
+
+#[web::post("/{cuid}/attachments")]
+pub async fn upload(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	mut payload: Multipart,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "attach files to").await?;
+
+	let used_bytes = match app_state.repositories.attachment.used_bytes(user_uuid.clone()).await {
+		Ok(used_bytes) => used_bytes as u64,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to check attachment quota")),
+	};
+
+	let max_bytes = max_upload_bytes();
+	let quota_bytes = quota_bytes();
+	let remaining_bytes = quota_bytes.saturating_sub(used_bytes);
+
+	while let Some(field) = payload.next().await {
+		let mut field = match field {
+			Ok(field) => field,
+			Err(_) => return Err(HttpError::bad_request("Invalid multipart body")),
+		};
+
+		let Some(filename) =
+			field.content_disposition().and_then(|disposition| disposition.get_filename().map(sanitize_filename))
+		else {
+			continue;
+		};
+		let content_type = field.content_type().to_string();
+
+		let mut data = vec![];
+		while let Some(chunk) = field.next().await {
+			let chunk = match chunk {
+				Ok(chunk) => chunk,
+				Err(_) => return Err(HttpError::bad_request("Invalid multipart body")),
+			};
+
+			data.extend_from_slice(&chunk);
+
+			if data.len() as u64 > max_bytes {
+				return Err(HttpError::payload_too_large(&format!(
+					"Attachments must not be larger than {} MB",
+					max_bytes / 1024 / 1024
+				)));
+			}
+
+			if data.len() as u64 > remaining_bytes {
+				return Err(HttpError::payload_too_large("Attachment quota exceeded"));
+			}
+		}
+
+		let attachment = match app_state
+			.repositories
+			.attachment
+			.create(cuid.clone(), user_uuid.clone(), filename, content_type, data.len() as i32, quota_bytes as i64)
+			.await
+		{
+			Ok(Some(attachment)) => attachment,
+			Ok(None) => return Err(HttpError::payload_too_large("Attachment quota exceeded")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to create attachment")),
+		};
+
+		if app_state.storage.put(&attachment.storage_key, data).await.is_err() {
+			let _ = app_state.repositories.attachment.delete(attachment.cuid).await;
+			return Err(HttpError::internal_server_error("Failed to store attachment"));
+		}
+
+		return Ok(HttpResponse::Created().json(&attachment));
+	}
+
+	Err(HttpError::bad_request("Missing file"))
+}
+
+#[web::get("/{cuid}/attachments/{attachment_cuid}")]
+pub async fn download(
+	req: HttpRequest,
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, attachment_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let attachment = find_attachment(&app_state, &cuid, attachment_cuid).await?;
+	let size = attachment.size as u64;
+
+	let byte_range = match req.headers().get(http::header::RANGE).and_then(|value| value.to_str().ok()) {
+		Some(spec) => parse_range(spec, size),
+		None => ByteRange::Full,
+	};
+
+	let (mut response, range) = match byte_range {
+		ByteRange::Full => (HttpResponse::Ok(), 0..size),
+		ByteRange::Partial(range) => {
+			let mut response = HttpResponse::PartialContent();
+			response.header(http::header::CONTENT_RANGE, format!("bytes {}-{}/{}", range.start, range.end - 1, size));
+			(response, range)
+		}
+		ByteRange::Unsatisfiable => {
+			return Ok(HttpResponse::build(http::StatusCode::RANGE_NOT_SATISFIABLE)
+				.header(http::header::CONTENT_RANGE, format!("bytes */{}", size))
+				.json(&json!({ "error": "Requested range not satisfiable" })))
+		}
+	};
+
+	let data = match app_state.storage.get(&attachment.storage_key, range).await {
+		Ok(data) => data,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to read attachment")),
+	};
+
+	Ok(response
+		.header(http::header::CONTENT_TYPE, attachment.content_type.as_str())
+		.header(http::header::CONTENT_DISPOSITION, content_disposition(&attachment.filename))
+		.header(http::header::ACCEPT_RANGES, "bytes")
+		.header(http::header::X_CONTENT_TYPE_OPTIONS, "nosniff")
+		.body(data))
+}
+
+#[web::delete("/{cuid}/attachments/{attachment_cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, attachment_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	let access = policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let attachment = find_attachment(&app_state, &cuid, attachment_cuid.clone()).await?;
+
+	if attachment.uploader_uuid != user_uuid && access != Access::Own {
+		return Err(HttpError::forbidden("You are not allowed to delete this attachment"));
+	}
+
+	let attachment = match app_state.repositories.attachment.delete(attachment_cuid).await {
+		Ok(attachment) => attachment,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete attachment")),
+	};
+
+	if let Err(e) = app_state.storage.delete(&attachment.storage_key).await {
+		error!("Failed to delete stored file {}: {}", attachment.storage_key, e);
+	}
+
+	Ok(HttpResponse::Ok().json(&attachment))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(find_all).service(upload).service(download).service(delete);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parse_range_reads_closed_open_and_suffix_ranges() {
+		assert_eq!(parse_range("bytes=0-99", 1000), ByteRange::Partial(0..100));
+		assert_eq!(parse_range("bytes=900-", 1000), ByteRange::Partial(900..1000));
+		assert_eq!(parse_range("bytes=-100", 1000), ByteRange::Partial(900..1000));
+		assert_eq!(parse_range("bytes=-2000", 1000), ByteRange::Partial(0..1000));
+	}
+
+	#[test]
+	fn parse_range_clamps_the_end_to_the_size() {
+		assert_eq!(parse_range("bytes=500-5000", 1000), ByteRange::Partial(500..1000));
+		assert_eq!(parse_range(&format!("bytes=0-{}", u64::MAX), 1000), ByteRange::Partial(0..1000));
+	}
+
+	#[test]
+	fn parse_range_rejects_ranges_outside_the_file() {
+		assert_eq!(parse_range("bytes=1000-", 1000), ByteRange::Unsatisfiable);
+		assert_eq!(parse_range("bytes=-0", 1000), ByteRange::Unsatisfiable);
+		assert_eq!(parse_range("bytes=0-", 0), ByteRange::Unsatisfiable);
+		assert_eq!(parse_range("bytes=-10", 0), ByteRange::Unsatisfiable);
+	}
+
+	#[test]
+	fn parse_range_serves_the_whole_file_for_unsupported_headers() {
+		assert_eq!(parse_range("bytes=0-1,5-9", 1000), ByteRange::Full);
+		assert_eq!(parse_range("bytes=9-5", 1000), ByteRange::Full);
+		assert_eq!(parse_range("items=0-9", 1000), ByteRange::Full);
+		assert_eq!(parse_range("bytes=a-b", 1000), ByteRange::Full);
+	}
+
+	#[test]
+	fn sanitize_filename_keeps_only_the_basename() {
+		assert_eq!(sanitize_filename("report.pdf"), "report.pdf");
+		assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+		assert_eq!(sanitize_filename("C:\\Users\\me\\notes.txt"), "notes.txt");
+	}
+
+	#[test]
+	fn sanitize_filename_strips_quotes_and_control_characters() {
+		assert_eq!(sanitize_filename("a\"b\r\n.txt"), "ab.txt");
+		assert_eq!(sanitize_filename("  spaced.txt  "), "spaced.txt");
+	}
+
+	#[test]
+	fn sanitize_filename_falls_back_for_empty_names() {
+		assert_eq!(sanitize_filename(""), "attachment");
+		assert_eq!(sanitize_filename("uploads/"), "attachment");
+		assert_eq!(sanitize_filename(".."), "attachment");
+	}
+}
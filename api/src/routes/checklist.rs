@@ -0,0 +1,152 @@
+use crate::{
+	error::HttpError,
+	policies::{self, Access},
+	repositories::{
+		checklist::{ChecklistItem, ChecklistItemChanges},
+		task::Task,
+	},
+	routes::user::Claims,
+	states::app::{AppState, AppStateType},
+};
+use ntex::web::{self, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChecklistItemInput {
+	pub text: String,
+	pub checked: Option<bool>,
+	pub position: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChecklistItemPatchInput {
+	pub text: Option<String>,
+	pub checked: Option<bool>,
+	pub position: Option<usize>,
+}
+
+async fn find_task(app_state: &AppState, cuid: String) -> Result<Task, HttpError> {
+	match app_state.repositories.task.find_one(cuid).await {
+		Ok(Some(task)) => Ok(task),
+		Ok(None) => Err(HttpError::not_found("Task not found")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find task")),
+	}
+}
+
+async fn find_item(app_state: &AppState, task_cuid: &str, cuid: String) -> Result<ChecklistItem, HttpError> {
+	match app_state.repositories.checklist.find_one(cuid).await {
+		Ok(Some(item)) if item.task_cuid == task_cuid => Ok(item),
+		Ok(_) => Err(HttpError::not_found("Checklist item not found")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find checklist item")),
+	}
+}
+
+fn validate_text(text: &str) -> Result<String, HttpError> {
+	let text = text.trim();
+	if text.is_empty() {
+		return Err(HttpError::bad_request("Checklist item text must not be empty"));
+	}
+
+	Ok(text.to_string())
+}
+
+#[web::get("/{cuid}/checklist")]
+pub async fn find_all(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let items = match app_state.repositories.checklist.find_by_task(cuid.clone()).await {
+		Ok(items) => items,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find checklist items")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "checklist": items })))
+}
+
+#[web::post("/{cuid}/checklist")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	item_input: web::types::Json<ChecklistItemInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	let ChecklistItemInput { text, checked, position } = item_input.into_inner();
+	let text = validate_text(&text)?;
+
+	let item = match app_state.repositories.checklist.create(cuid.clone(), text, checked.unwrap_or(false), position).await {
+		Ok(Some(item)) => item,
+		Ok(None) => return Err(HttpError::conflict("Failed to place checklist item, please retry")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create checklist item")),
+	};
+
+	Ok(HttpResponse::Created().json(&item))
+}
+
+#[web::patch("/{cuid}/checklist/{item_cuid}")]
+pub async fn update(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+	item_input: web::types::Json<ChecklistItemPatchInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, item_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	find_item(&app_state, &cuid, item_cuid.clone()).await?;
+
+	let ChecklistItemPatchInput { text, checked, position } = item_input.into_inner();
+	let changes = ChecklistItemChanges { text: text.as_deref().map(validate_text).transpose()?, checked, index: position };
+
+	let item = match app_state.repositories.checklist.update(item_cuid, cuid, changes).await {
+		Ok(Some(item)) => item,
+		Ok(None) => return Err(HttpError::conflict("Failed to place checklist item, please retry")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update checklist item")),
+	};
+
+	Ok(HttpResponse::Ok().json(&item))
+}
+
+#[web::delete("/{cuid}/checklist/{item_cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, item_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	find_item(&app_state, &cuid, item_cuid.clone()).await?;
+
+	let item = match app_state.repositories.checklist.delete(item_cuid).await {
+		Ok(item) => item,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete checklist item")),
+	};
+
+	Ok(HttpResponse::Ok().json(&item))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(find_all).service(create).service(update).service(delete);
+}
@@ -0,0 +1,144 @@
+use crate::{
+	error::HttpError,
+	policies::{self, Access},
+	repositories::task::Task,
+	routes::user::Claims,
+	states::app::{AppState, AppStateType},
+};
+use ntex::web::{self, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommentInput {
+	pub body: String,
+}
+
+async fn find_task(app_state: &AppState, cuid: String) -> Result<Task, HttpError> {
+	match app_state.repositories.task.find_one(cuid).await {
+		Ok(Some(task)) => Ok(task),
+		Ok(None) => Err(HttpError::not_found("Task not found")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find task")),
+	}
+}
+
+fn validate_comment(input: &CommentInput) -> Result<(), HttpError> {
+	if input.body.trim().is_empty() {
+		return Err(HttpError::bad_request("Comment must not be empty"));
+	}
+
+	Ok(())
+}
+
+#[web::get("/{cuid}/comments")]
+pub async fn find_all(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let comments = match app_state.repositories.comment.find_by_task(cuid.clone()).await {
+		Ok(comments) => comments,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find comments")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "comments": comments })))
+}
+
+#[web::post("/{cuid}/comments")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	comment_input: web::types::Json<CommentInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "comment on").await?;
+
+	validate_comment(&comment_input)?;
+
+	let comment =
+		match app_state.repositories.comment.create(cuid.clone(), user_uuid, comment_input.body.trim().to_string()).await {
+			Ok(comment) => comment,
+			Err(_) => return Err(HttpError::internal_server_error("Failed to create comment")),
+		};
+
+	Ok(HttpResponse::Created().json(&comment))
+}
+
+#[web::put("/{cuid}/comments/{comment_cuid}")]
+pub async fn update(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+	comment_input: web::types::Json<CommentInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, comment_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "comment on").await?;
+
+	let comment = match app_state.repositories.comment.find_one(comment_cuid.clone()).await {
+		Ok(Some(comment)) if comment.task_cuid == cuid => comment,
+		Ok(_) => return Err(HttpError::not_found("Comment not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find comment")),
+	};
+
+	if comment.author_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to update this comment"));
+	}
+
+	validate_comment(&comment_input)?;
+
+	let comment = match app_state.repositories.comment.update(comment_cuid, comment_input.body.trim().to_string()).await {
+		Ok(comment) => comment,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update comment")),
+	};
+
+	Ok(HttpResponse::Ok().json(&comment))
+}
+
+#[web::delete("/{cuid}/comments/{comment_cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, comment_cuid) = path.into_inner();
+
+	let task = find_task(&app_state, cuid.clone()).await?;
+	let access = policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let comment = match app_state.repositories.comment.find_one(comment_cuid.clone()).await {
+		Ok(Some(comment)) if comment.task_cuid == cuid => comment,
+		Ok(_) => return Err(HttpError::not_found("Comment not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find comment")),
+	};
+
+	if comment.author_uuid != user_uuid && access != Access::Own {
+		return Err(HttpError::forbidden("You are not allowed to delete this comment"));
+	}
+
+	let comment = match app_state.repositories.comment.delete(comment_cuid).await {
+		Ok(comment) => comment,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete comment")),
+	};
+
+	Ok(HttpResponse::Ok().json(&comment))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(find_all).service(create).service(update).service(delete);
+}
@@ -1,2 +1,9 @@
+pub mod attachment;
+pub mod checklist;
+pub mod comment;
+pub mod project;
+pub mod status;
+pub mod tag;
 pub mod task;
 pub mod user;
+pub mod well_known;
@@ -0,0 +1,314 @@
+use crate::{
+	error::HttpError,
+	policies::{self, Access},
+	routes::{
+		task::{find_grantee, find_tasks, ShareInput, TaskQuery},
+		user::Claims,
+	},
+	states::app::AppStateType,
+};
+use ntex::web::{self, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectInput {
+	pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectQuery {
+	#[serde(default)]
+	pub archived: bool,
+}
+
+#[web::get("/")]
+pub async fn find_all(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	query: web::types::Query<ProjectQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let projects = match app_state.repositories.project.find_all(user_uuid, query.archived).await {
+		Ok(projects) => projects,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find projects")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "projects": projects })))
+}
+
+#[web::post("/")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	project_input: web::types::Json<ProjectInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	if project_input.name.trim().is_empty() {
+		return Err(HttpError::bad_request("Project name must not be empty"));
+	}
+
+	let project = match app_state.repositories.project.create(project_input.name.trim().to_string(), user_uuid).await {
+		Ok(project) => project,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create project")),
+	};
+
+	Ok(HttpResponse::Created().json(&project))
+}
+
+#[web::put("/{cuid}")]
+pub async fn update(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	project_input: web::types::Json<ProjectInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::Edit, "update").await?;
+
+	if project_input.name.trim().is_empty() {
+		return Err(HttpError::bad_request("Project name must not be empty"));
+	}
+
+	let project = match app_state.repositories.project.update(cuid.clone(), project_input.name.trim().to_string()).await {
+		Ok(project) => project,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update project")),
+	};
+
+	Ok(HttpResponse::Ok().json(&project))
+}
+
+#[web::post("/{cuid}/archive")]
+pub async fn archive(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::Own, "archive").await?;
+
+	let project = match app_state.repositories.project.set_archived(cuid.clone(), true).await {
+		Ok(project) => project,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to archive project")),
+	};
+
+	Ok(HttpResponse::Ok().json(&project))
+}
+
+#[web::post("/{cuid}/unarchive")]
+pub async fn unarchive(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::Own, "unarchive").await?;
+
+	let project = match app_state.repositories.project.set_archived(cuid.clone(), false).await {
+		Ok(project) => project,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to unarchive project")),
+	};
+
+	Ok(HttpResponse::Ok().json(&project))
+}
+
+#[web::get("/{cuid}/tasks")]
+pub async fn find_tasks_in_project(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	query: web::types::Query<TaskQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::View, "view").await?;
+
+	let mut query = query.into_inner();
+	query.project = Some(project.cuid.clone());
+
+	find_tasks(&app_state, project.user_uuid, query).await
+}
+
+#[web::get("/{cuid}/shares")]
+pub async fn find_shares(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::View, "view").await?;
+
+	let shares = match app_state.repositories.share.find_by_project(cuid.clone()).await {
+		Ok(shares) => shares,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find shares")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "shares": shares })))
+}
+
+#[web::post("/{cuid}/shares")]
+pub async fn share(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	share_input: web::types::Json<ShareInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::Own, "share").await?;
+
+	let ShareInput { email, role } = share_input.into_inner();
+	if let Some(grantee) = find_grantee(&app_state, &project.user_uuid, email.clone()).await? {
+		if app_state.repositories.share.share_project(cuid.clone(), grantee.uuid, role).await.is_err() {
+			return Err(HttpError::internal_server_error("Failed to share project"));
+		}
+	}
+
+	Ok(HttpResponse::Created().json(&json!({ "email": email, "role": role })))
+}
+
+#[web::delete("/{cuid}/shares/{share_cuid}")]
+pub async fn revoke_share(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, share_cuid) = path.into_inner();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	let share = match app_state.repositories.share.find_one(share_cuid.clone()).await {
+		Ok(Some(share)) if share.project_cuid.as_deref() == Some(&cuid) => share,
+		Ok(_) => return Err(HttpError::not_found("Share not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find share")),
+	};
+
+	if share.user_uuid != user_uuid {
+		policies::project::authorize(&app_state, &user_uuid, &project, Access::Own, "share").await?;
+	}
+
+	let share = match app_state.repositories.share.delete(share_cuid).await {
+		Ok(share) => share,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to revoke share")),
+	};
+
+	Ok(HttpResponse::Ok().json(&share))
+}
+
+#[web::delete("/{cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::Own, "delete").await?;
+
+	let project = match app_state.repositories.project.delete(cuid.clone()).await {
+		Ok(project) => project,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete project")),
+	};
+
+	Ok(HttpResponse::Ok().json(&project))
+}
+
+#[web::get("/{cuid}")]
+pub async fn find_one(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let project = match app_state.repositories.project.find_one(cuid.clone()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::not_found("Project not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(&app_state, &user_uuid, &project, Access::View, "view").await?;
+
+	Ok(HttpResponse::Ok().json(&project))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(
+		web::scope("/project")
+			.service(find_all)
+			.service(create)
+			.service(update)
+			.service(archive)
+			.service(unarchive)
+			.service(find_tasks_in_project)
+			.service(find_shares)
+			.service(share)
+			.service(revoke_share)
+			.service(delete)
+			.service(find_one),
+	);
+}
@@ -0,0 +1,180 @@
+use crate::{
+	error::HttpError,
+	routes::user::Claims,
+	states::app::{AppState, AppStateType},
+};
+use ntex::web::{self, HttpResponse};
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StatusInput {
+	pub name: String,
+	pub position: Option<i32>,
+	#[serde(default)]
+	pub completed: bool,
+	// Omitting the transitions allows moving to any status, an empty list makes the status final.
+	pub transitions: Option<Vec<String>>,
+}
+
+async fn validate_status(
+	app_state: &AppState,
+	user_uuid: String,
+	cuid: Option<&str>,
+	input: &StatusInput,
+) -> Result<(), HttpError> {
+	if input.name.trim().is_empty() {
+		return Err(HttpError::bad_request("Status name must not be empty"));
+	}
+
+	if input.position.is_some_and(|position| position < 0) {
+		return Err(HttpError::bad_request("Status position must not be negative"));
+	}
+
+	let Some(mut transitions) = input.transitions.clone() else {
+		return Ok(());
+	};
+
+	if cuid.is_some_and(|cuid| transitions.iter().any(|transition| transition == cuid)) {
+		return Err(HttpError::bad_request("A status cannot transition to itself"));
+	}
+
+	transitions.sort();
+	transitions.dedup();
+
+	match app_state.repositories.status.count_owned(user_uuid, transitions.clone()).await {
+		Ok(count) if count == transitions.len() as i64 => Ok(()),
+		Ok(_) => Err(HttpError::bad_request("One or more transition statuses do not exist")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find statuses")),
+	}
+}
+
+#[web::get("/")]
+pub async fn find_all(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let statuses = match app_state.repositories.status.find_all(user_uuid).await {
+		Ok(statuses) => statuses,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find statuses")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "statuses": statuses })))
+}
+
+#[web::post("/")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	status_input: web::types::Json<StatusInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	validate_status(&app_state, user_uuid.clone(), None, &status_input).await?;
+
+	let StatusInput { name, position, completed, transitions } = status_input.into_inner();
+
+	let status =
+		match app_state.repositories.status.create(user_uuid, name.trim().to_string(), position, completed, transitions).await {
+			Ok(status) => status,
+			Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Err(HttpError::conflict("Status already exists")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to create status")),
+		};
+
+	Ok(HttpResponse::Created().json(&status))
+}
+
+#[web::put("/{cuid}")]
+pub async fn update(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	status_input: web::types::Json<StatusInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let status = match app_state.repositories.status.find_one(cuid.clone()).await {
+		Ok(Some(status)) => status,
+		Ok(None) => return Err(HttpError::not_found("Status not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find status")),
+	};
+
+	if status.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to update this status"));
+	}
+
+	validate_status(&app_state, user_uuid.clone(), Some(&status.cuid), &status_input).await?;
+
+	let StatusInput { name, position, completed, transitions } = status_input.into_inner();
+
+	let updated =
+		match app_state.repositories.status.update(cuid.clone(), name.trim().to_string(), position, completed, transitions).await {
+			Ok(status) => status,
+			Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Err(HttpError::conflict("Status already exists")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to update status")),
+		};
+
+	if updated.completed != status.completed
+		&& app_state.repositories.task.sync_status_done(cuid.clone(), updated.completed, user_uuid).await.is_err()
+	{
+		return Err(HttpError::internal_server_error("Failed to update tasks in this status"));
+	}
+
+	Ok(HttpResponse::Ok().json(&updated))
+}
+
+#[web::delete("/{cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let status = match app_state.repositories.status.find_one(cuid.clone()).await {
+		Ok(Some(status)) => status,
+		Ok(None) => return Err(HttpError::not_found("Status not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find status")),
+	};
+
+	if status.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to delete this status"));
+	}
+
+	let status = match app_state.repositories.status.delete(cuid.clone()).await {
+		Ok(status) => status,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete status")),
+	};
+
+	Ok(HttpResponse::Ok().json(&status))
+}
+
+#[web::get("/{cuid}")]
+pub async fn find_one(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let status = match app_state.repositories.status.find_one(cuid.clone()).await {
+		Ok(Some(status)) => status,
+		Ok(None) => return Err(HttpError::not_found("Status not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find status")),
+	};
+
+	if status.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to view this status"));
+	}
+
+	Ok(HttpResponse::Ok().json(&status))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(web::scope("/status").service(find_all).service(create).service(update).service(delete).service(find_one));
+}
@@ -0,0 +1,142 @@
+use crate::{error::HttpError, routes::user::Claims, states::app::AppStateType};
+use ntex::web::{self, HttpResponse};
+use prisma_client_rust::prisma_errors::query_engine::UniqueKeyViolation;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TagInput {
+	pub name: String,
+	pub color: String,
+}
+
+fn validate_tag(input: &TagInput) -> Result<(), HttpError> {
+	if input.name.trim().is_empty() {
+		return Err(HttpError::bad_request("Tag name must not be empty"));
+	}
+
+	let hex = input.color.strip_prefix('#').unwrap_or_default();
+	if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+		return Err(HttpError::bad_request("Tag color must be a hex color like #1E90FF"));
+	}
+
+	Ok(())
+}
+
+#[web::get("/")]
+pub async fn find_all(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tags = match app_state.repositories.tag.find_all(user_uuid).await {
+		Ok(tags) => tags,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tags")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "tags": tags })))
+}
+
+#[web::post("/")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	tag_input: web::types::Json<TagInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	validate_tag(&tag_input)?;
+
+	let tag = match app_state.repositories.tag.create(tag_input.name.trim().to_string(), tag_input.color.clone(), user_uuid).await {
+		Ok(tag) => tag,
+		Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Err(HttpError::conflict("Tag already exists")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create tag")),
+	};
+
+	Ok(HttpResponse::Created().json(&tag))
+}
+
+#[web::put("/{cuid}")]
+pub async fn update(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	tag_input: web::types::Json<TagInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tag = match app_state.repositories.tag.find_one(cuid.clone()).await {
+		Ok(Some(tag)) => tag,
+		Ok(None) => return Err(HttpError::not_found("Tag not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tag")),
+	};
+
+	if tag.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to update this tag"));
+	}
+
+	validate_tag(&tag_input)?;
+
+	let tag = match app_state.repositories.tag.update(cuid.clone(), tag_input.name.trim().to_string(), tag_input.color.clone()).await
+	{
+		Ok(tag) => tag,
+		Err(e) if e.is_prisma_error::<UniqueKeyViolation>() => return Err(HttpError::conflict("Tag already exists")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update tag")),
+	};
+
+	Ok(HttpResponse::Ok().json(&tag))
+}
+
+#[web::delete("/{cuid}")]
+pub async fn delete(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tag = match app_state.repositories.tag.find_one(cuid.clone()).await {
+		Ok(Some(tag)) => tag,
+		Ok(None) => return Err(HttpError::not_found("Tag not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tag")),
+	};
+
+	if tag.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to delete this tag"));
+	}
+
+	let tag = match app_state.repositories.tag.delete(cuid.clone()).await {
+		Ok(tag) => tag,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to delete tag")),
+	};
+
+	Ok(HttpResponse::Ok().json(&tag))
+}
+
+#[web::get("/{cuid}")]
+pub async fn find_one(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tag = match app_state.repositories.tag.find_one(cuid.clone()).await {
+		Ok(Some(tag)) => tag,
+		Ok(None) => return Err(HttpError::not_found("Tag not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tag")),
+	};
+
+	if tag.user_uuid != user_uuid {
+		return Err(HttpError::forbidden("You are not allowed to view this tag"));
+	}
+
+	Ok(HttpResponse::Ok().json(&tag))
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(web::scope("/tag").service(find_all).service(create).service(update).service(delete).service(find_one));
+}
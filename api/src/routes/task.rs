@@ -1,58 +1,1107 @@
-use crate::{error::HttpError, routes::user::Claims, states::app::AppStateType};
-use ntex::web::{self, HttpResponse};
+use crate::{
+	db::{Priority, ShareRole},
+	error::HttpError,
+	policies::{self, Access},
+	repositories::{
+		status::Status,
+		task::{
+			BulkAction, BulkError, ChecklistCount, SearchHit, SortOrder, TagMode, Task, TaskChanges, TaskCursor, TaskFields,
+			TaskFilter, TaskSort, TaskUpdate,
+		},
+		user::User,
+	},
+	routes::{self, user::Claims},
+	states::app::{AppState, AppStateType},
+	storage,
+	utils::{
+		cursor::{decode_cursor, encode_cursor},
+		datetime::{db_now_datetime, parse_timezone, start_of_day},
+		patch::double_option,
+		recurrence::{is_valid_rrule, next_occurrence},
+	},
+};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, Utc};
+use chrono_tz::Tz;
+use ntex::{
+	http,
+	web::{self, HttpRequest, HttpResponse},
+};
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const DEFAULT_PAGE_LIMIT: i64 = 50;
+const MAX_PAGE_LIMIT: i64 = 100;
+const MAX_BULK_ITEMS: usize = 500;
+const DEFAULT_SEARCH_LIMIT: i64 = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct TaskQuery {
+	pub limit: Option<i64>,
+	pub cursor: Option<String>,
+	pub done: Option<bool>,
+	pub priority: Option<Priority>,
+	pub status: Option<String>,
+	pub project: Option<String>,
+	pub parent: Option<String>,
+	#[serde(default)]
+	pub root: bool,
+	pub tag: Option<String>,
+	#[serde(default)]
+	pub tag_mode: TagMode,
+	pub title: Option<String>,
+	pub created_after: Option<DateTime<FixedOffset>>,
+	pub created_before: Option<DateTime<FixedOffset>>,
+	pub updated_after: Option<DateTime<FixedOffset>>,
+	pub updated_before: Option<DateTime<FixedOffset>>,
+	#[serde(default)]
+	pub sort: TaskSort,
+	#[serde(default)]
+	pub order: SortOrder,
+}
+
+impl From<TaskQuery> for TaskFilter {
+	fn from(query: TaskQuery) -> Self {
+		Self {
+			done: query.done,
+			priority: query.priority,
+			status_cuid: query.status,
+			project_cuid: query.project,
+			parent_cuid: query.parent,
+			root_only: query.root,
+			tags: query
+				.tag
+				.map(|tags| tags.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from).collect())
+				.unwrap_or_default(),
+			tag_mode: query.tag_mode,
+			title: query.title,
+			created_after: query.created_after,
+			created_before: query.created_before,
+			updated_after: query.updated_after,
+			updated_before: query.updated_before,
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionRule {
+	#[default]
+	Reject,
+	Cascade,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChildrenDeleteRule {
+	#[default]
+	Cascade,
+	Detach,
+	Reject,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditScope {
+	#[default]
+	Occurrence,
+	Series,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskUpdateQuery {
+	#[serde(default)]
+	pub scope: EditScope,
+	#[serde(default)]
+	pub completion: CompletionRule,
+	#[serde(default)]
+	pub auto_complete_parent: bool,
+	#[serde(default)]
+	pub enforce_blockers: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskDeleteQuery {
+	#[serde(default)]
+	pub children: ChildrenDeleteRule,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+	pub q: String,
+	pub limit: Option<i64>,
+	#[serde(default)]
+	pub offset: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DueQuery {
+	pub tz: Option<String>,
+	pub done: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BulkMode {
+	#[default]
+	AllOrNothing,
+	BestEffort,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BulkOperation {
+	Complete { cuids: Vec<String> },
+	Uncomplete { cuids: Vec<String> },
+	Delete { cuids: Vec<String> },
+	MoveToProject { cuids: Vec<String>, project_cuid: Option<String> },
+	AddTag { cuids: Vec<String>, tag_cuid: String },
+}
+
+impl BulkOperation {
+	fn cuids(&self) -> &[String] {
+		match self {
+			Self::Complete { cuids }
+			| Self::Uncomplete { cuids }
+			| Self::Delete { cuids }
+			| Self::MoveToProject { cuids, .. }
+			| Self::AddTag { cuids, .. } => cuids,
+		}
+	}
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskBulkInput {
+	#[serde(default)]
+	pub mode: BulkMode,
+	pub operations: Vec<BulkOperation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkResult {
+	pub operation: usize,
+	pub cuid: String,
+	pub status: u16,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub error: Option<Value>,
+}
+
+impl BulkResult {
+	fn fail(&mut self, err: HttpError) {
+		self.status = err.status.as_u16();
+		self.error = err.message.get("error").cloned();
+	}
+}
+
+// The route checks every item up front, but the repository repeats the checks inside its transaction.
+fn bulk_failure(error: &BulkError) -> HttpError {
+	match error {
+		BulkError::NotFound(_) => HttpError::not_found("Task not found"),
+		BulkError::Forbidden(_) => HttpError::forbidden("You are not allowed to update this task"),
+		BulkError::OpenSubtasks(_) => HttpError::conflict("Task has open subtasks"),
+		BulkError::InvalidTransition(_) => HttpError::bad_request("Task cannot move to its default status"),
+		BulkError::Query(_) => HttpError::internal_server_error("Failed to apply bulk operation"),
+	}
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TaskCreateInput {
 	pub title: String,
 	pub description: String,
+	pub priority: Option<Priority>,
+	pub due_at: Option<DateTime<FixedOffset>>,
+	pub remind_at: Option<DateTime<FixedOffset>>,
+	pub rrule: Option<String>,
+	pub tags: Option<Vec<String>>,
+	pub status_cuid: Option<String>,
+	pub project_cuid: Option<String>,
+	pub parent_cuid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskUpdateInput {
+	pub title: String,
+	pub description: String,
+	pub done: bool,
+	pub priority: Option<Priority>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub due_at: Option<Option<DateTime<FixedOffset>>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub remind_at: Option<Option<DateTime<FixedOffset>>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub rrule: Option<Option<String>>,
+	pub tags: Option<Vec<String>>,
+	pub status_cuid: Option<String>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub project_cuid: Option<Option<String>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub parent_cuid: Option<Option<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaskPatchInput {
+	pub title: Option<String>,
+	pub description: Option<String>,
+	pub done: Option<bool>,
+	pub priority: Option<Priority>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub due_at: Option<Option<DateTime<FixedOffset>>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub remind_at: Option<Option<DateTime<FixedOffset>>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub rrule: Option<Option<String>>,
+	pub tags: Option<Vec<String>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub status_cuid: Option<Option<String>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub project_cuid: Option<Option<String>>,
+	#[serde(default, deserialize_with = "double_option")]
+	pub parent_cuid: Option<Option<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskProjectInput {
+	pub project_cuid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskMoveInput {
+	pub before: Option<String>,
+	pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskDependencyInput {
+	pub blocker_cuid: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TaskResponse {
+	#[serde(flatten)]
+	pub task: Task,
+	pub blocked: bool,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub checklist_progress: Option<String>,
+}
+
+impl From<Task> for TaskResponse {
+	fn from(task: Task) -> Self {
+		let blocked = task.blocked_by.as_ref().is_some_and(|blockers| !blockers.is_empty());
+
+		Self { task, blocked, checklist_progress: None }
+	}
+}
+
+impl TaskResponse {
+	fn with_checklist(mut self, count: Option<&ChecklistCount>) -> Self {
+		self.checklist_progress = count.map(|count| format!("{}/{}", count.checked, count.total));
+		self
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ShareInput {
+	pub email: String,
+	pub role: ShareRole,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+	pub task: TaskResponse,
+	pub rank: f64,
+	pub title_snippet: String,
+	pub description_snippet: String,
+}
+
+impl From<SearchHit> for SearchResult {
+	fn from(hit: SearchHit) -> Self {
+		Self {
+			task: TaskResponse::from(hit.task),
+			rank: hit.rank,
+			title_snippet: hit.title_snippet,
+			description_snippet: hit.description_snippet,
+		}
+	}
+}
+
+impl From<TaskCreateInput> for TaskFields {
+	fn from(input: TaskCreateInput) -> Self {
+		Self {
+			title: input.title,
+			description: input.description,
+			done: false,
+			priority: input.priority.unwrap_or(Priority::None),
+			due_at: input.due_at,
+			remind_at: input.remind_at,
+			rrule: input.rrule,
+			tags: input.tags,
+			status_cuid: input.status_cuid,
+			project_cuid: input.project_cuid,
+			parent_cuid: input.parent_cuid,
+		}
+	}
+}
+
+impl From<TaskUpdateInput> for TaskChanges {
+	fn from(input: TaskUpdateInput) -> Self {
+		Self {
+			title: Some(input.title),
+			description: Some(input.description),
+			done: Some(input.done),
+			priority: input.priority,
+			due_at: input.due_at,
+			remind_at: input.remind_at,
+			rrule: input.rrule,
+			tags: input.tags,
+			status_cuid: input.status_cuid.map(Some),
+			project_cuid: input.project_cuid,
+			parent_cuid: input.parent_cuid,
+		}
+	}
+}
+
+impl From<TaskPatchInput> for TaskChanges {
+	fn from(input: TaskPatchInput) -> Self {
+		Self {
+			title: input.title,
+			description: input.description,
+			done: input.done,
+			priority: input.priority,
+			due_at: input.due_at,
+			remind_at: input.remind_at,
+			rrule: input.rrule,
+			tags: input.tags,
+			status_cuid: input.status_cuid,
+			project_cuid: input.project_cuid,
+			parent_cuid: input.parent_cuid,
+		}
+	}
+}
+
+fn entity_tag(task: &Task) -> String {
+	format!("\"{}\"", task.version)
+}
+
+fn parse_if_match(req: &HttpRequest) -> Result<Option<Vec<i32>>, HttpError> {
+	let Some(value) = req.headers().get(http::header::IF_MATCH) else {
+		return Ok(None);
+	};
+
+	let value = value.to_str().map_err(|_| HttpError::bad_request("Invalid If-Match header"))?;
+	if value.trim() == "*" {
+		return Ok(None);
+	}
+
+	// If-Match uses the strong comparison, so weak tags never match.
+	value
+		.split(',')
+		.map(str::trim)
+		.filter(|tag| !tag.starts_with("W/"))
+		.map(|tag| tag.trim_matches('"').parse::<i32>())
+		.collect::<Result<Vec<_>, _>>()
+		.map(Some)
+		.map_err(|_| HttpError::bad_request("Invalid If-Match header"))
+}
+
+fn validate_schedule(
+	due_at: Option<DateTime<FixedOffset>>,
+	remind_at: Option<DateTime<FixedOffset>>,
+) -> Result<(), HttpError> {
+	if let (Some(due_at), Some(remind_at)) = (due_at, remind_at) {
+		if remind_at > due_at {
+			return Err(HttpError::bad_request("Reminder must not be after the due date"));
+		}
+	}
+
+	Ok(())
+}
+
+fn validate_recurrence(rrule: Option<&str>, due_at: Option<DateTime<FixedOffset>>) -> Result<(), HttpError> {
+	let Some(rrule) = rrule else {
+		return Ok(());
+	};
+
+	let Some(due_at) = due_at else {
+		return Err(HttpError::bad_request("Recurring tasks must have a due date"));
+	};
+
+	if !is_valid_rrule(rrule, due_at) {
+		return Err(HttpError::bad_request("Invalid recurrence rule"));
+	}
+
+	Ok(())
+}
+
+async fn validate_tags(app_state: &AppState, user_uuid: String, tags: Option<&[String]>) -> Result<(), HttpError> {
+	let Some(tags) = tags else {
+		return Ok(());
+	};
+
+	let mut cuids = tags.to_vec();
+	cuids.sort();
+	cuids.dedup();
+
+	match app_state.repositories.tag.count_owned(user_uuid, cuids.clone()).await {
+		Ok(count) if count == cuids.len() as i64 => Ok(()),
+		Ok(_) => Err(HttpError::bad_request("One or more tags do not exist")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find tags")),
+	}
+}
+
+async fn validate_project(app_state: &AppState, user_uuid: &str, project_cuid: Option<&str>) -> Result<(), HttpError> {
+	let Some(project_cuid) = project_cuid else {
+		return Ok(());
+	};
+
+	let project = match app_state.repositories.project.find_one(project_cuid.to_string()).await {
+		Ok(Some(project)) => project,
+		Ok(None) => return Err(HttpError::bad_request("Project does not exist")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find project")),
+	};
+
+	policies::project::authorize(app_state, user_uuid, &project, Access::Edit, "add tasks to").await?;
+
+	if project.archived_at.is_some() {
+		return Err(HttpError::bad_request("Cannot add tasks to an archived project"));
+	}
+
+	Ok(())
+}
+
+async fn validate_parent(app_state: &AppState, user_uuid: &str, parent_cuid: Option<&str>) -> Result<(), HttpError> {
+	let Some(parent_cuid) = parent_cuid else {
+		return Ok(());
+	};
+
+	let parent = match app_state.repositories.task.find_one(parent_cuid.to_string()).await {
+		Ok(Some(parent)) => parent,
+		Ok(None) => return Err(HttpError::bad_request("Parent task does not exist")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find parent task")),
+	};
+
+	policies::task::authorize(app_state, user_uuid, &parent, Access::Edit, "add subtasks to").await?;
+
+	Ok(())
+}
+
+async fn find_status(app_state: &AppState, user_uuid: String, status_cuid: &str) -> Result<Status, HttpError> {
+	match app_state.repositories.status.find_one(status_cuid.to_string()).await {
+		Ok(Some(status)) if status.user_uuid == user_uuid => Ok(status),
+		Ok(_) => Err(HttpError::bad_request("Status does not exist")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find status")),
+	}
+}
+
+async fn find_default_status(app_state: &AppState, user_uuid: String, completed: bool) -> Result<Option<Status>, HttpError> {
+	match app_state.repositories.status.find_default(user_uuid, completed).await {
+		Ok(status) => Ok(status),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find status")),
+	}
+}
+
+async fn validate_transition(app_state: &AppState, user_uuid: String, task: &Task, status: &Status) -> Result<(), HttpError> {
+	let Some(current_cuid) = task.status_cuid.as_deref().filter(|current_cuid| *current_cuid != status.cuid) else {
+		return Ok(());
+	};
+
+	match app_state.repositories.status.can_transition(current_cuid.to_string(), status.cuid.clone()).await {
+		Ok(true) => Ok(()),
+		Ok(false) => {
+			let current = find_status(app_state, user_uuid, current_cuid).await?;
+			Err(HttpError::bad_request(&format!("Cannot move a task from {} to {}", current.name, status.name)))
+		}
+		Err(_) => Err(HttpError::internal_server_error("Failed to validate status transition")),
+	}
+}
+
+async fn validate_done_transition(app_state: &AppState, task: &Task, done: bool) -> Result<(), HttpError> {
+	if task.done == done {
+		return Ok(());
+	}
+
+	match find_default_status(app_state, task.user_uuid.clone(), done).await? {
+		Some(status) => validate_transition(app_state, task.user_uuid.clone(), task, &status).await,
+		None => Ok(()),
+	}
+}
+
+async fn apply_status(
+	app_state: &AppState,
+	user_uuid: String,
+	task: &Task,
+	changes: &mut TaskChanges,
+) -> Result<(), HttpError> {
+	let status = match changes.status_cuid.clone() {
+		Some(Some(status_cuid)) => {
+			let status = find_status(app_state, user_uuid.clone(), &status_cuid).await?;
+			if changes.done.is_some_and(|done| done != status.completed) {
+				return Err(HttpError::bad_request("Done does not match the completed flag of the status"));
+			}
+			status
+		}
+		Some(None) => return Ok(()),
+		None => {
+			let Some(done) = changes.done.filter(|done| *done != task.done) else {
+				return Ok(());
+			};
+			let Some(status) = find_default_status(app_state, user_uuid.clone(), done).await? else {
+				return Ok(());
+			};
+			changes.status_cuid = Some(Some(status.cuid.clone()));
+			status
+		}
+	};
+
+	validate_transition(app_state, user_uuid, task, &status).await?;
+	changes.done = Some(status.completed);
+
+	Ok(())
+}
+
+// Returns None for unknown emails, which callers answer like a successful share so that accounts cannot be enumerated.
+pub async fn find_grantee(app_state: &AppState, owner_uuid: &str, email: String) -> Result<Option<User>, HttpError> {
+	let user = match app_state.repositories.user.find_by_email(email).await {
+		Ok(Some(user)) => user,
+		Ok(None) => return Ok(None),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find user")),
+	};
+
+	if user.uuid == owner_uuid {
+		return Err(HttpError::bad_request("Cannot share with the owner"));
+	}
+
+	Ok(Some(user))
+}
+
+async fn resolve_timezone(app_state: &AppState, user_uuid: String, tz: Option<&str>) -> Result<Tz, HttpError> {
+	let name = match tz {
+		Some(tz) => tz.to_string(),
+		None => match app_state.repositories.user.find_by_uuid(user_uuid).await {
+			Ok(Some(user)) => user.timezone,
+			Ok(None) => return Err(HttpError::not_found("User not found")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to get user")),
+		},
+	};
+
+	parse_timezone(&name).ok_or_else(|| HttpError::bad_request("Invalid timezone"))
+}
+
+pub async fn find_tasks(app_state: &AppState, user_uuid: String, query: TaskQuery) -> Result<HttpResponse, HttpError> {
+	let limit = query.limit.unwrap_or(DEFAULT_PAGE_LIMIT);
+	if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+		return Err(HttpError::bad_request(&format!("Limit must be between 1 and {}", MAX_PAGE_LIMIT)));
+	}
+
+	let (sort, order) = (query.sort, query.order);
+
+	let cursor = match &query.cursor {
+		Some(cursor) => match decode_cursor::<TaskCursor>(cursor) {
+			Some(cursor) if cursor.sort == sort && cursor.order == order => Some(cursor),
+			Some(_) => return Err(HttpError::bad_request("Cursor does not match the sort order")),
+			None => return Err(HttpError::bad_request("Invalid cursor")),
+		},
+		None => None,
+	};
+
+	let page = match app_state.repositories.task.find_all(user_uuid, query.into(), sort, order, limit, cursor).await {
+		Ok(page) => page,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({
+		"tasks": page
+			.tasks
+			.into_iter()
+			.map(|task| {
+				let count = page.checklist.get(&task.cuid);
+				TaskResponse::from(task).with_checklist(count)
+			})
+			.collect::<Vec<_>>(),
+		"next_cursor": page.next_cursor.as_ref().map(encode_cursor),
+		"total": page.total,
+	})))
+}
+
+#[web::get("/")]
+pub async fn find_all(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	query: web::types::Query<TaskQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	find_tasks(&app_state, user_uuid, query.into_inner()).await
+}
+
+#[web::get("/search")]
+pub async fn search(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	query: web::types::Query<SearchQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let query = query.into_inner();
+
+	if query.q.trim().is_empty() {
+		return Err(HttpError::bad_request("Search query must not be empty"));
+	}
+
+	let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+	if !(1..=MAX_PAGE_LIMIT).contains(&limit) {
+		return Err(HttpError::bad_request(&format!("Limit must be between 1 and {}", MAX_PAGE_LIMIT)));
+	}
+	if query.offset < 0 {
+		return Err(HttpError::bad_request("Offset must not be negative"));
+	}
+
+	let results = match app_state.repositories.task.search(user_uuid, query.q, limit, query.offset).await {
+		Ok(results) => results,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to search tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "results": results.into_iter().map(SearchResult::from).collect::<Vec<_>>() })))
+}
+
+#[web::get("/overdue")]
+pub async fn find_overdue(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tasks = match app_state.repositories.task.find_due(user_uuid, None, db_now_datetime(), Some(false)).await {
+		Ok(tasks) => tasks,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "tasks": tasks.into_iter().map(TaskResponse::from).collect::<Vec<_>>() })))
+}
+
+#[web::get("/due/today")]
+pub async fn find_due_today(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	query: web::types::Query<DueQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tz = resolve_timezone(&app_state, user_uuid.clone(), query.tz.as_deref()).await?;
+	let today = Utc::now().with_timezone(&tz).date_naive();
+	let from = start_of_day(today, tz);
+	let to = start_of_day(today + Duration::days(1), tz);
+
+	let tasks = match app_state.repositories.task.find_due(user_uuid, Some(from), to, query.done).await {
+		Ok(tasks) => tasks,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({
+		"tasks": tasks.into_iter().map(TaskResponse::from).collect::<Vec<_>>(),
+		"from": from,
+		"to": to,
+	})))
+}
+
+#[web::get("/due/week")]
+pub async fn find_due_this_week(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	query: web::types::Query<DueQuery>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tz = resolve_timezone(&app_state, user_uuid.clone(), query.tz.as_deref()).await?;
+	let today = Utc::now().with_timezone(&tz).date_naive();
+	let monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+	let from = start_of_day(monday, tz);
+	let to = start_of_day(monday + Duration::days(7), tz);
+
+	let tasks = match app_state.repositories.task.find_due(user_uuid, Some(from), to, query.done).await {
+		Ok(tasks) => tasks,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({
+		"tasks": tasks.into_iter().map(TaskResponse::from).collect::<Vec<_>>(),
+		"from": from,
+		"to": to,
+	})))
+}
+
+#[web::post("/")]
+pub async fn create(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	task_input: web::types::Json<TaskCreateInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	validate_schedule(task_input.due_at, task_input.remind_at)?;
+	validate_recurrence(task_input.rrule.as_deref(), task_input.due_at)?;
+	validate_tags(&app_state, user_uuid.clone(), task_input.tags.as_deref()).await?;
+	validate_project(&app_state, &user_uuid, task_input.project_cuid.as_deref()).await?;
+	validate_parent(&app_state, &user_uuid, task_input.parent_cuid.as_deref()).await?;
+
+	let mut fields: TaskFields = task_input.into_inner().into();
+	let status = match &fields.status_cuid {
+		Some(status_cuid) => Some(find_status(&app_state, user_uuid.clone(), status_cuid).await?),
+		None => find_default_status(&app_state, user_uuid.clone(), false).await?,
+	};
+	if let Some(status) = status {
+		fields.done = status.completed;
+		fields.status_cuid = Some(status.cuid);
+	}
+
+	let task = match app_state.repositories.task.create(user_uuid, fields).await {
+		Ok(task) => task,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create task")),
+	};
+
+	Ok(HttpResponse::Created().json(&TaskResponse::from(task)))
+}
+
+async fn update_task(
+	app_state: &AppState,
+	user_uuid: String,
+	cuid: String,
+	query: &TaskUpdateQuery,
+	mut changes: TaskChanges,
+	if_match: Option<Vec<i32>>,
+) -> Result<Task, HttpError> {
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	let expected_version = match if_match {
+		Some(versions) if versions.contains(&task.version) => Some(task.version),
+		Some(_) => return Err(HttpError::precondition_failed("Task has been modified")),
+		None => None,
+	};
+
+	let due_at = changes.due_at.unwrap_or(task.due_at);
+	let remind_at = changes.remind_at.unwrap_or(task.remind_at);
+	let rrule = changes.rrule.clone().unwrap_or_else(|| task.rrule.clone());
+
+	validate_schedule(due_at, remind_at)?;
+	validate_recurrence(rrule.as_deref(), due_at)?;
+	validate_tags(app_state, task.user_uuid.clone(), changes.tags.as_deref()).await?;
+	if let Some(project_cuid) = &changes.project_cuid {
+		if *project_cuid != task.project_cuid {
+			validate_project(app_state, &user_uuid, project_cuid.as_deref()).await?;
+		}
+	}
+	if let Some(parent_cuid) = &changes.parent_cuid {
+		if *parent_cuid != task.parent_cuid {
+			validate_parent(app_state, &user_uuid, parent_cuid.as_deref()).await?;
+		}
+	}
+
+	apply_status(app_state, task.user_uuid.clone(), &task, &mut changes).await?;
+
+	let completing = changes.done == Some(true) && !task.done;
+	if completing && query.enforce_blockers {
+		match app_state.repositories.task.count_open_blockers(task.cuid.clone()).await {
+			Ok(0) => {}
+			Ok(_) => return Err(HttpError::conflict("Task is blocked by open tasks")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to find blocking tasks")),
+		}
+	}
+	let complete_descendants = completing && complete_children(app_state, task.cuid.clone(), query.completion).await?;
+
+	let series_cuid = task.series_cuid.clone().unwrap_or_else(|| task.cuid.clone());
+
+	let task = match app_state
+		.repositories
+		.task
+		.update(cuid, changes, expected_version, complete_descendants, user_uuid.clone())
+		.await
+	{
+		Ok(TaskUpdate::Updated(task)) => task,
+		Ok(TaskUpdate::NotFound) => return Err(HttpError::not_found("Task not found")),
+		Ok(TaskUpdate::Modified) => return Err(HttpError::precondition_failed("Task has been modified")),
+		Ok(TaskUpdate::Cycle) => return Err(HttpError::bad_request("A task cannot be nested under itself or its subtasks")),
+		Ok(TaskUpdate::InvalidTransition) => return Err(HttpError::conflict("A subtask cannot be moved to a completed status")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update task")),
+	};
+
+	if let EditScope::Series = query.scope {
+		if app_state
+			.repositories
+			.task
+			.update_open_series(
+				series_cuid,
+				task.title.clone(),
+				task.description.clone(),
+				task.priority.clone(),
+				task.rrule.clone(),
+				user_uuid.clone(),
+			)
+			.await
+			.is_err()
+		{
+			return Err(HttpError::internal_server_error("Failed to update task series"));
+		}
+	}
+
+	if completing {
+		if let (Some(rrule), Some(due_at)) = (&task.rrule, task.due_at) {
+			let tz = resolve_timezone(app_state, task.user_uuid.clone(), None).await?;
+			if let Some(next_due_at) = next_occurrence(rrule, due_at, tz) {
+				if app_state.repositories.task.spawn_next_occurrence(&task, next_due_at, user_uuid.clone()).await.is_err() {
+					return Err(HttpError::internal_server_error("Failed to create next occurrence"));
+				}
+			}
+		}
+	}
+
+	if task.done
+		&& query.auto_complete_parent
+		&& app_state.repositories.task.complete_finished_ancestors(task.parent_cuid.clone(), user_uuid).await.is_err()
+	{
+		return Err(HttpError::internal_server_error("Failed to complete parent task"));
+	}
+
+	Ok(task)
+}
+
+async fn prepare_bulk_action(
+	app_state: &AppState,
+	user_uuid: &str,
+	task: Option<&Task>,
+	operation: &BulkOperation,
+	operation_check: &Result<(), HttpError>,
+) -> Result<BulkAction, HttpError> {
+	let Some(task) = task else {
+		return Err(HttpError::not_found("Task not found"));
+	};
+
+	match operation {
+		BulkOperation::Delete { .. } => policies::task::authorize(app_state, user_uuid, task, Access::Own, "delete").await?,
+		_ => policies::task::authorize(app_state, user_uuid, task, Access::Edit, "update").await?,
+	};
+
+	operation_check.clone()?;
+
+	match operation {
+		BulkOperation::Complete { .. } => {
+			if task.children.as_ref().is_some_and(|children| children.iter().any(|child| !child.done)) {
+				return Err(HttpError::conflict("Task has open subtasks"));
+			}
+			validate_done_transition(app_state, task, true).await?;
+			Ok(BulkAction::SetDone(true))
+		}
+		BulkOperation::Uncomplete { .. } => {
+			validate_done_transition(app_state, task, false).await?;
+			Ok(BulkAction::SetDone(false))
+		}
+		BulkOperation::Delete { .. } => match app_state.repositories.task.find_descendant_cuids(task.cuid.clone()).await {
+			Ok(descendants) => Ok(BulkAction::Trash(descendants)),
+			Err(_) => Err(HttpError::internal_server_error("Failed to find subtasks")),
+		},
+		BulkOperation::MoveToProject { project_cuid, .. } => Ok(BulkAction::MoveToProject(project_cuid.clone())),
+		BulkOperation::AddTag { tag_cuid, .. } => {
+			validate_tags(app_state, task.user_uuid.clone(), Some(std::slice::from_ref(tag_cuid))).await?;
+			Ok(BulkAction::AddTag(tag_cuid.clone()))
+		}
+	}
+}
+
+#[web::post("/bulk")]
+pub async fn bulk(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	bulk_input: web::types::Json<TaskBulkInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let bulk_input = bulk_input.into_inner();
+
+	let mut cuids: Vec<String> = bulk_input.operations.iter().flat_map(|operation| operation.cuids()).cloned().collect();
+	if cuids.is_empty() || cuids.len() > MAX_BULK_ITEMS {
+		return Err(HttpError::bad_request(&format!("Bulk requests must contain between 1 and {} items", MAX_BULK_ITEMS)));
+	}
+
+	cuids.sort();
+	cuids.dedup();
+
+	let tasks: HashMap<String, Task> = match app_state.repositories.task.find_many(cuids).await {
+		Ok(tasks) => tasks.into_iter().map(|task| (task.cuid.clone(), task)).collect(),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	let mut results = vec![];
+	let mut items = vec![];
+
+	for (index, operation) in bulk_input.operations.iter().enumerate() {
+		let operation_check = match operation {
+			BulkOperation::MoveToProject { project_cuid, .. } => {
+				validate_project(&app_state, &user_uuid, project_cuid.as_deref()).await
+			}
+			_ => Ok(()),
+		};
+
+		for cuid in operation.cuids() {
+			let mut result =
+				BulkResult { operation: index, cuid: cuid.clone(), status: http::StatusCode::OK.as_u16(), error: None };
+			match prepare_bulk_action(&app_state, &user_uuid, tasks.get(cuid), operation, &operation_check).await {
+				Ok(action) => items.push((results.len(), cuid.clone(), action)),
+				Err(err) => result.fail(err),
+			}
+
+			results.push(result);
+		}
+	}
+
+	let failed = results.iter().any(|result| result.error.is_some());
+	if failed && matches!(bulk_input.mode, BulkMode::AllOrNothing) {
+		return Ok(HttpResponse::BadRequest().json(&json!({ "applied": false, "results": results })));
+	}
+
+	match bulk_input.mode {
+		BulkMode::AllOrNothing => {
+			let items = items.into_iter().map(|(_, cuid, action)| (cuid, action)).collect();
+			match app_state.repositories.task.apply_bulk(items, user_uuid).await {
+				Ok(()) => {}
+				Err(error) => {
+					let Some(cuid) = error.cuid() else {
+						return Err(HttpError::internal_server_error("Failed to apply bulk operations"));
+					};
+					for result in results.iter_mut().filter(|result| result.cuid == cuid) {
+						result.fail(bulk_failure(&error));
+					}
+					return Ok(HttpResponse::BadRequest().json(&json!({ "applied": false, "results": results })));
+				}
+			}
+		}
+		BulkMode::BestEffort => {
+			for (index, cuid, action) in items {
+				match app_state.repositories.task.apply_bulk_item(cuid, action, user_uuid.clone()).await {
+					Ok(()) => {}
+					Err(error) => results[index].fail(bulk_failure(&error)),
+				}
+			}
+		}
+	}
+
+	Ok(HttpResponse::Ok().json(&json!({ "applied": true, "results": results })))
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct TaskUpdateInput {
-	pub title: String,
-	pub description: String,
-	pub done: bool,
+#[web::put("/{cuid}")]
+pub async fn update(
+	req: HttpRequest,
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	query: web::types::Query<TaskUpdateQuery>,
+	task_input: web::types::Json<TaskUpdateInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let if_match = parse_if_match(&req)?;
+	let task = update_task(&app_state, user_uuid, cuid.clone(), &query, task_input.into_inner().into(), if_match).await?;
+
+	Ok(HttpResponse::Ok().header(http::header::ETAG, entity_tag(&task)).json(&TaskResponse::from(task)))
 }
 
-#[web::get("/")]
-pub async fn find_all(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+#[web::patch("/{cuid}")]
+pub async fn patch(
+	req: HttpRequest,
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	query: web::types::Query<TaskUpdateQuery>,
+	task_input: web::types::Json<TaskPatchInput>,
+) -> Result<HttpResponse, HttpError> {
 	let app_state = state.read().await;
 	let user_uuid = claims.get_user_uuid();
 
-	let tasks = match app_state.repositories.task.find_all(user_uuid).await {
-		Ok(tasks) => tasks,
-		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	let if_match = parse_if_match(&req)?;
+	let task = update_task(&app_state, user_uuid, cuid.clone(), &query, task_input.into_inner().into(), if_match).await?;
+
+	Ok(HttpResponse::Ok().header(http::header::ETAG, entity_tag(&task)).json(&TaskResponse::from(task)))
+}
+
+// Returns whether the open subtasks of a task being completed have to be completed with it.
+async fn complete_children(app_state: &AppState, cuid: String, rule: CompletionRule) -> Result<bool, HttpError> {
+	let open_children = match app_state.repositories.task.count_open_children(cuid).await {
+		Ok(count) => count,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find subtasks")),
 	};
 
-	Ok(HttpResponse::Ok().json(&json!({ "tasks": tasks })))
+	if open_children == 0 {
+		return Ok(false);
+	}
+
+	match rule {
+		CompletionRule::Reject => Err(HttpError::conflict("Task has open subtasks")),
+		CompletionRule::Cascade => Ok(true),
+	}
 }
 
-#[web::post("/")]
-pub async fn create(
+#[web::put("/{cuid}/project")]
+pub async fn move_to_project(
 	state: web::types::State<AppStateType>,
 	claims: Claims,
-	task_input: web::types::Json<TaskCreateInput>,
+	cuid: web::types::Path<String>,
+	project_input: web::types::Json<TaskProjectInput>,
 ) -> Result<HttpResponse, HttpError> {
 	let app_state = state.read().await;
 	let user_uuid = claims.get_user_uuid();
 
-	let task = match app_state.repositories.task.create(task_input.title.clone(), task_input.description.clone(), user_uuid).await
-	{
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	validate_project(&app_state, &user_uuid, project_input.project_cuid.as_deref()).await?;
+
+	let project_cuid = project_input.into_inner().project_cuid;
+	let task = match app_state.repositories.task.move_to_project(cuid.clone(), project_cuid, user_uuid).await {
 		Ok(task) => task,
-		Err(_) => return Err(HttpError::internal_server_error("Failed to create task")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to move task")),
 	};
 
-	Ok(HttpResponse::Created().json(&task))
+	Ok(HttpResponse::Ok().json(&TaskResponse::from(task)))
 }
 
-#[web::put("/{cuid}")]
-pub async fn update(
+async fn validate_anchor(
+	app_state: &AppState,
+	user_uuid: String,
+	cuid: &str,
+	anchor_cuid: Option<&str>,
+) -> Result<(), HttpError> {
+	let Some(anchor_cuid) = anchor_cuid else {
+		return Ok(());
+	};
+
+	if anchor_cuid == cuid {
+		return Err(HttpError::bad_request("A task cannot be moved relative to itself"));
+	}
+
+	match app_state.repositories.task.find_one(anchor_cuid.to_string()).await {
+		Ok(Some(anchor)) if anchor.user_uuid == user_uuid => Ok(()),
+		Ok(_) => Err(HttpError::bad_request("Anchor task does not exist")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to find anchor task")),
+	}
+}
+
+#[web::post("/{cuid}/move")]
+pub async fn move_task(
 	state: web::types::State<AppStateType>,
 	claims: Claims,
 	cuid: web::types::Path<String>,
-	task_input: web::types::Json<TaskUpdateInput>,
+	move_input: web::types::Json<TaskMoveInput>,
 ) -> Result<HttpResponse, HttpError> {
 	let app_state = state.read().await;
 	let user_uuid = claims.get_user_uuid();
@@ -63,28 +1112,32 @@ pub async fn update(
 		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
 	};
 
-	if task.user_uuid != user_uuid {
-		return Err(HttpError::forbidden("You are not allowed to update this task"));
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	let TaskMoveInput { before, after } = move_input.into_inner();
+	if before.is_none() && after.is_none() {
+		return Err(HttpError::bad_request("Either before or after must be provided"));
 	}
 
-	let task = match app_state
-		.repositories
-		.task
-		.update(cuid.clone(), task_input.title.clone(), task_input.description.clone(), task_input.done)
-		.await
-	{
-		Ok(task) => task,
-		Err(_) => return Err(HttpError::internal_server_error("Failed to update task")),
+	validate_anchor(&app_state, task.user_uuid.clone(), &task.cuid, before.as_deref()).await?;
+	validate_anchor(&app_state, task.user_uuid.clone(), &task.cuid, after.as_deref()).await?;
+
+	let task = match app_state.repositories.task.move_between(cuid.clone(), task.user_uuid, before, after).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::bad_request("The after task must come before the before task")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to move task")),
 	};
 
-	Ok(HttpResponse::Ok().json(&task))
+	Ok(HttpResponse::Ok().json(&TaskResponse::from(task)))
 }
 
 #[web::delete("/{cuid}")]
 pub async fn delete(
+	req: HttpRequest,
 	state: web::types::State<AppStateType>,
 	claims: Claims,
 	cuid: web::types::Path<String>,
+	query: web::types::Query<TaskDeleteQuery>,
 ) -> Result<HttpResponse, HttpError> {
 	let app_state = state.read().await;
 	let user_uuid = claims.get_user_uuid();
@@ -95,16 +1148,315 @@ pub async fn delete(
 		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
 	};
 
-	if task.user_uuid != user_uuid {
-		return Err(HttpError::forbidden("You are not allowed to delete this task"));
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Own, "delete").await?;
+
+	let if_match = parse_if_match(&req)?;
+	if if_match.as_ref().is_some_and(|versions| !versions.contains(&task.version)) {
+		return Err(HttpError::precondition_failed("Task has been modified"));
 	}
 
-	let task = match app_state.repositories.task.delete(cuid.clone()).await {
+	let descendants = match query.children {
+		ChildrenDeleteRule::Cascade => match app_state.repositories.task.find_descendant_cuids(cuid.clone()).await {
+			Ok(descendants) => descendants,
+			Err(_) => return Err(HttpError::internal_server_error("Failed to find subtasks")),
+		},
+		ChildrenDeleteRule::Reject => {
+			if task.children.as_ref().is_some_and(|children| !children.is_empty()) {
+				return Err(HttpError::conflict("Task has subtasks"));
+			}
+			vec![]
+		}
+		ChildrenDeleteRule::Detach => vec![],
+	};
+
+	let expected_version = if_match.map(|_| task.version);
+	let detach_children = matches!(query.children, ChildrenDeleteRule::Detach);
+
+	let task =
+		match app_state.repositories.task.trash(cuid.clone(), expected_version, descendants, detach_children, user_uuid).await {
+			Ok(Some(task)) => task,
+			Ok(None) => return Err(HttpError::precondition_failed("Task has been modified")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to delete task")),
+		};
+
+	Ok(HttpResponse::Ok().json(&TaskResponse::from(task)))
+}
+
+#[web::get("/trash")]
+pub async fn find_trash(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tasks = match app_state.repositories.task.find_trashed(user_uuid).await {
+		Ok(tasks) => tasks,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "tasks": tasks.into_iter().map(TaskResponse::from).collect::<Vec<_>>() })))
+}
+
+#[web::post("/{cuid}/restore")]
+pub async fn restore(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one_trashed(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found in trash")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Own, "restore").await?;
+
+	let task = match app_state.repositories.task.restore(task, user_uuid).await {
+		Ok(task) => task,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to restore task")),
+	};
+
+	Ok(HttpResponse::Ok().json(&TaskResponse::from(task)))
+}
+
+#[web::delete("/{cuid}/purge")]
+pub async fn purge(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one_trashed(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found in trash")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Own, "delete").await?;
+
+	let storage_keys = match app_state.repositories.attachment.find_keys_in_subtree(cuid.clone()).await {
+		Ok(storage_keys) => storage_keys,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find attachments")),
+	};
+
+	let task = match app_state.repositories.task.purge(cuid.clone()).await {
 		Ok(task) => task,
 		Err(_) => return Err(HttpError::internal_server_error("Failed to delete task")),
 	};
 
-	Ok(HttpResponse::Ok().json(&task))
+	storage::delete_all(app_state.storage.as_ref(), storage_keys).await;
+
+	Ok(HttpResponse::Ok().json(&TaskResponse::from(task)))
+}
+
+#[web::post("/{cuid}/dependencies")]
+pub async fn add_dependency(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	dependency_input: web::types::Json<TaskDependencyInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	let blocker_cuid = dependency_input.into_inner().blocker_cuid;
+	if blocker_cuid == task.cuid {
+		return Err(HttpError::bad_request("A task cannot block itself"));
+	}
+
+	match app_state.repositories.task.find_one(blocker_cuid.clone()).await {
+		Ok(Some(blocker)) if blocker.user_uuid == task.user_uuid => {}
+		Ok(_) => return Err(HttpError::bad_request("Blocking task does not exist")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find blocking task")),
+	}
+
+	match app_state.repositories.task.is_blocked_by(&task.cuid, blocker_cuid.clone()).await {
+		Ok(false) => {}
+		Ok(true) => return Err(HttpError::conflict("Dependency would create a cycle")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to validate dependency")),
+	}
+
+	let task = match app_state.repositories.task.add_blocker(cuid.clone(), blocker_cuid, user_uuid).await {
+		Ok(task) => task,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to add dependency")),
+	};
+
+	Ok(HttpResponse::Ok().header(http::header::ETAG, entity_tag(&task)).json(&TaskResponse::from(task)))
+}
+
+#[web::delete("/{cuid}/dependencies/{blocker_cuid}")]
+pub async fn remove_dependency(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, blocker_cuid) = path.into_inner();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Edit, "update").await?;
+
+	let task = match app_state.repositories.task.remove_blocker(cuid, blocker_cuid, user_uuid).await {
+		Ok(task) => task,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to remove dependency")),
+	};
+
+	Ok(HttpResponse::Ok().header(http::header::ETAG, entity_tag(&task)).json(&TaskResponse::from(task)))
+}
+
+#[web::get("/shared")]
+pub async fn find_shared(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let tasks = match app_state.repositories.task.find_shared(user_uuid).await {
+		Ok(tasks) => tasks,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find tasks")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "tasks": tasks.into_iter().map(TaskResponse::from).collect::<Vec<_>>() })))
+}
+
+#[web::get("/{cuid}/shares")]
+pub async fn find_shares(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+
+	let shares = match app_state.repositories.share.find_by_task(cuid.clone()).await {
+		Ok(shares) => shares,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find shares")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "shares": shares })))
+}
+
+#[web::post("/{cuid}/shares")]
+pub async fn share(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+	share_input: web::types::Json<ShareInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::Own, "share").await?;
+
+	let ShareInput { email, role } = share_input.into_inner();
+	if let Some(grantee) = find_grantee(&app_state, &task.user_uuid, email.clone()).await? {
+		if app_state.repositories.share.share_task(cuid.clone(), grantee.uuid, role).await.is_err() {
+			return Err(HttpError::internal_server_error("Failed to share task"));
+		}
+	}
+
+	Ok(HttpResponse::Created().json(&json!({ "email": email, "role": role })))
+}
+
+#[web::delete("/{cuid}/shares/{share_cuid}")]
+pub async fn revoke_share(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	path: web::types::Path<(String, String)>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+	let (cuid, share_cuid) = path.into_inner();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => task,
+		Ok(None) => return Err(HttpError::not_found("Task not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	let share = match app_state.repositories.share.find_one(share_cuid.clone()).await {
+		Ok(Some(share)) if share.task_cuid.as_deref() == Some(&cuid) => share,
+		Ok(_) => return Err(HttpError::not_found("Share not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find share")),
+	};
+
+	if share.user_uuid != user_uuid {
+		policies::task::authorize(&app_state, &user_uuid, &task, Access::Own, "share").await?;
+	}
+
+	let share = match app_state.repositories.share.delete(share_cuid).await {
+		Ok(share) => share,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to revoke share")),
+	};
+
+	Ok(HttpResponse::Ok().json(&share))
+}
+
+#[web::get("/{cuid}/history")]
+pub async fn history(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	cuid: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let user_uuid = claims.get_user_uuid();
+
+	let task = match app_state.repositories.task.find_one(cuid.clone()).await {
+		Ok(Some(task)) => Some(task),
+		Ok(None) => match app_state.repositories.task.find_one_trashed(cuid.clone()).await {
+			Ok(task) => task,
+			Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+		},
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
+	};
+
+	// Once a task is purged only its owner can still read the history.
+	match task {
+		Some(task) => {
+			policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
+		}
+		None => match app_state.repositories.task_event.find_owner(cuid.clone()).await {
+			Ok(Some(owner_uuid)) if owner_uuid == user_uuid => {}
+			Ok(_) => return Err(HttpError::not_found("Task not found")),
+			Err(_) => return Err(HttpError::internal_server_error("Failed to find task history")),
+		},
+	}
+
+	let events = match app_state.repositories.task_event.find_by_task(cuid.clone()).await {
+		Ok(events) => events,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find task history")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "events": events })))
 }
 
 #[web::get("/{cuid}")]
@@ -122,13 +1474,39 @@ pub async fn find_one(
 		Err(_) => return Err(HttpError::internal_server_error("Failed to find task")),
 	};
 
-	if task.clone().user_uuid != user_uuid {
-		return Err(HttpError::forbidden("You are not allowed to view this task"));
-	}
+	policies::task::authorize(&app_state, &user_uuid, &task, Access::View, "view").await?;
 
-	Ok(HttpResponse::Ok().json(&task))
+	Ok(HttpResponse::Ok().header(http::header::ETAG, entity_tag(&task)).json(&TaskResponse::from(task)))
 }
 
 pub fn init(config: &mut web::ServiceConfig) {
-	config.service(web::scope("/task").service(find_all).service(create).service(update).service(delete).service(find_one));
+	config.service(
+		web::scope("/task")
+			.service(find_all)
+			.service(search)
+			.service(find_overdue)
+			.service(find_due_today)
+			.service(find_due_this_week)
+			.service(find_trash)
+			.service(find_shared)
+			.service(create)
+			.service(bulk)
+			.service(update)
+			.service(patch)
+			.service(move_to_project)
+			.service(move_task)
+			.service(delete)
+			.service(restore)
+			.service(purge)
+			.service(add_dependency)
+			.service(remove_dependency)
+			.service(find_shares)
+			.service(share)
+			.service(revoke_share)
+			.service(history)
+			.configure(routes::comment::init)
+			.configure(routes::attachment::init)
+			.configure(routes::checklist::init)
+			.service(find_one),
+	);
 }
@@ -1,26 +1,50 @@
-use std::convert::Infallible;
-
-use crate::{error::HttpError, states::app::AppStateType};
+use crate::{
+	error::HttpError,
+	mailer::Mail,
+	middlewares::jwt::AuthFailure,
+	repositories::{session::Session, user::User},
+	states::app::{AppState, AppStateType},
+	utils::{
+		datetime::{db_now_datetime, parse_timezone},
+		token::{generate_token, hash_token},
+	},
+};
 
 use chrono;
-use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use ntex::{
-	http::Payload,
+	http::{self, Payload},
 	web::{self, ErrorRenderer, FromRequest, HttpRequest, HttpResponse},
 };
 use serde::{Deserialize, Serialize};
-use serde_json::json;
+use serde_json::{json, Value};
+
+const DEFAULT_ACCESS_TOKEN_TTL_MINUTES: i64 = 15;
+const DEFAULT_REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const DEFAULT_PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+pub fn access_token_ttl() -> chrono::Duration {
+	let minutes = std::env::var("ACCESS_TOKEN_TTL_MINUTES").ok().and_then(|minutes| minutes.parse().ok());
+	chrono::Duration::minutes(minutes.unwrap_or(DEFAULT_ACCESS_TOKEN_TTL_MINUTES))
+}
+
+pub fn refresh_token_ttl() -> chrono::Duration {
+	let days = std::env::var("REFRESH_TOKEN_TTL_DAYS").ok().and_then(|days| days.parse().ok());
+	chrono::Duration::days(days.unwrap_or(DEFAULT_REFRESH_TOKEN_TTL_DAYS))
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
 	uuid: String,
 	email: String,
+	#[serde(rename = "sid")]
+	session_family: String,
 	exp: usize,
 }
 
 impl Claims {
-	fn new(uuid: String, email: String) -> Self {
-		Self { uuid, email, exp: (chrono::Utc::now() + chrono::Duration::days(1)).timestamp() as usize }
+	fn new(uuid: String, email: String, session_family: String) -> Self {
+		Self { uuid, email, session_family, exp: (chrono::Utc::now() + access_token_ttl()).timestamp() as usize }
 	}
 
 	pub fn get_user_uuid(&self) -> String {
@@ -31,17 +55,44 @@ impl Claims {
 		self.email.clone()
 	}
 
+	pub fn get_session_family(&self) -> String {
+		self.session_family.clone()
+	}
+
 	pub fn get_expiration(&self) -> usize {
 		self.exp
 	}
 }
 
+// For routes in the JWT middleware's public list that act on a user when there is one. Only a missing access token
+// yields None, an expired, invalid or revoked one is still rejected.
+pub struct OptionalClaims(pub Option<Claims>);
+
+impl<Err: ErrorRenderer> FromRequest<Err> for OptionalClaims {
+	type Error = HttpError;
+
+	#[inline]
+	async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<OptionalClaims, HttpError> {
+		if let Some(claims) = req.extensions().get::<Claims>() {
+			return Ok(OptionalClaims(Some(claims.clone())));
+		}
+
+		match req.extensions().get::<AuthFailure>().copied() {
+			None | Some(AuthFailure::Missing) => Ok(OptionalClaims(None)),
+			Some(failure) => Err(failure.to_error()),
+		}
+	}
+}
+
 impl<Err: ErrorRenderer> FromRequest<Err> for Claims {
-	type Error = Infallible;
+	type Error = HttpError;
 
 	#[inline]
-	async fn from_request(req: &HttpRequest, _: &mut Payload) -> Result<Claims, Infallible> {
-		Ok(req.extensions().get::<Claims>().unwrap().clone())
+	async fn from_request(req: &HttpRequest, payload: &mut Payload) -> Result<Claims, HttpError> {
+		match <OptionalClaims as FromRequest<Err>>::from_request(req, payload).await? {
+			OptionalClaims(Some(claims)) => Ok(claims),
+			OptionalClaims(None) => Err(AuthFailure::Missing.to_error()),
+		}
 	}
 }
 
@@ -50,6 +101,7 @@ struct UserCreateInput {
 	name: String,
 	email: String,
 	password: String,
+	timezone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -58,6 +110,38 @@ struct UserLoginInput {
 	password: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+struct UserRefreshInput {
+	refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct UserLogoutInput {
+	refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct UserPasswordInput {
+	current_password: String,
+	new_password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct UserForgotPasswordInput {
+	email: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct UserResetPasswordInput {
+	token: String,
+	new_password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+struct UserTimezoneInput {
+	timezone: String,
+}
+
 #[web::post("/register")]
 async fn create_user(
 	state: web::types::State<AppStateType>,
@@ -65,10 +149,15 @@ async fn create_user(
 ) -> Result<HttpResponse, HttpError> {
 	let app_state = state.read().await;
 
+	let timezone = user_input.timezone.clone().unwrap_or_else(|| "UTC".to_string());
+	if parse_timezone(&timezone).is_none() {
+		return Err(HttpError::bad_request("Invalid timezone"));
+	}
+
 	let user = match app_state
 		.repositories
 		.user
-		.create(user_input.name.clone(), user_input.email.clone(), user_input.password.clone())
+		.create(user_input.name.clone(), user_input.email.clone(), user_input.password.clone(), timezone)
 		.await
 	{
 		Ok(user) => user,
@@ -78,8 +167,48 @@ async fn create_user(
 	Ok(HttpResponse::Created().json(&json!({ "name": user.name, "email": user.email })))
 }
 
+fn validate_password(password: &str) -> Result<(), HttpError> {
+	if password.chars().count() < MIN_PASSWORD_LENGTH {
+		return Err(HttpError::bad_request(&format!("Password must be at least {} characters long", MIN_PASSWORD_LENGTH)));
+	}
+
+	Ok(())
+}
+
+fn password_reset_mail(email: String, token: &str) -> Mail {
+	let body = match std::env::var("PASSWORD_RESET_URL") {
+		Ok(url) => format!("Use the link below to choose a new password:\n\n{}?token={}", url, token),
+		Err(_) => format!("Use the token below to choose a new password:\n\n{}", token),
+	};
+
+	Mail {
+		to: email,
+		subject: "Reset your password".to_string(),
+		body: format!("{}\n\nIf you did not ask for a password reset, you can ignore this message.", body),
+	}
+}
+
+fn issue_tokens(app_state: &AppState, user: &User, session: &Session, refresh_token: String) -> Result<Value, HttpError> {
+	let claims = Claims::new(user.uuid.clone(), user.email.clone(), session.family_cuid.clone());
+	let token = match app_state.keys.encode(&claims) {
+		Ok(token) => token,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create token")),
+	};
+
+	Ok(json!({
+		"token": token,
+		"expires_in": access_token_ttl().num_seconds(),
+		"refresh_token": refresh_token,
+		"refresh_expires_at": session.expires_at,
+		"name": user.name,
+		"email": user.email,
+		"uuid": user.uuid,
+	}))
+}
+
 #[web::post("/login")]
 async fn login_user(
+	req: HttpRequest,
 	state: web::types::State<AppStateType>,
 	user_input: web::types::Json<UserLoginInput>,
 ) -> Result<HttpResponse, HttpError> {
@@ -89,19 +218,242 @@ async fn login_user(
 	{
 		Ok(Some(user)) => user,
 		Ok(None) => return Err(HttpError::unauthorized("Invalid credentials")),
-		Err(_) => return Err(HttpError::unauthorized("Invalid credentials")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to verify credentials")),
 	};
 
-	let claims = Claims::new(user.uuid.clone(), user.email.clone());
-	let header = Header::new(Algorithm::HS256);
-	let key = EncodingKey::from_secret(app_state.secret.as_ref());
+	let user_agent = req.headers().get(http::header::USER_AGENT).and_then(|value| value.to_str().ok()).map(str::to_string);
+	let ip_address = req.peer_addr().map(|addr| addr.ip().to_string());
 
-	let token = match encode(&header, &claims, &key) {
-		Ok(token) => token,
-		Err(_) => return Err(HttpError::internal_server_error("Failed to create token")),
+	let refresh_token = generate_token();
+	let session = match app_state
+		.repositories
+		.session
+		.create(user.uuid.clone(), hash_token(&refresh_token), db_now_datetime() + refresh_token_ttl(), user_agent, ip_address)
+		.await
+	{
+		Ok(session) => session,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to create session")),
 	};
 
-	Ok(HttpResponse::Ok().json(&json!({ "token": token , "name": user.name, "email": user.email, "uuid": user.uuid })))
+	Ok(HttpResponse::Ok().json(&issue_tokens(&app_state, &user, &session, refresh_token)?))
+}
+
+#[web::post("/refresh")]
+async fn refresh_session(
+	state: web::types::State<AppStateType>,
+	refresh_input: web::types::Json<UserRefreshInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	let session = match app_state.repositories.session.find_by_token_hash(hash_token(&refresh_input.refresh_token)).await {
+		Ok(Some(session)) => session,
+		Ok(None) => return Err(HttpError::unauthorized("Invalid refresh token")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find session")),
+	};
+
+	if session.revoked_at.is_some() || session.expires_at < db_now_datetime() {
+		return Err(HttpError::unauthorized("Invalid refresh token"));
+	}
+
+	let family_cuid = session.family_cuid.clone();
+	let reused = session.rotated_at.is_some();
+
+	let refresh_token = generate_token();
+	let rotated = if reused {
+		None
+	} else {
+		match app_state
+			.repositories
+			.session
+			.rotate(session, hash_token(&refresh_token), db_now_datetime() + refresh_token_ttl())
+			.await
+		{
+			Ok(rotated) => rotated,
+			Err(_) => return Err(HttpError::internal_server_error("Failed to refresh session")),
+		}
+	};
+
+	// A refresh token that was already exchanged may have been stolen, so the whole family is revoked.
+	let Some(session) = rotated else {
+		if app_state.repositories.session.revoke_family(family_cuid.clone()).await.is_err() {
+			return Err(HttpError::internal_server_error("Failed to revoke session"));
+		}
+		warn!("Refresh token reuse detected, revoked session family {}", family_cuid);
+
+		return Err(HttpError::unauthorized("Refresh token has already been used"));
+	};
+
+	let user = match app_state.repositories.user.find_by_uuid(session.user_uuid.clone()).await {
+		Ok(Some(user)) => user,
+		Ok(None) => return Err(HttpError::unauthorized("Invalid refresh token")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to get user")),
+	};
+
+	Ok(HttpResponse::Ok().json(&issue_tokens(&app_state, &user, &session, refresh_token)?))
+}
+
+#[web::post("/logout")]
+async fn logout_user(
+	state: web::types::State<AppStateType>,
+	logout_input: web::types::Json<UserLogoutInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	// The refresh token identifies the session on its own, so logging out still works once the access token expired.
+	let session = match app_state.repositories.session.find_by_token_hash(hash_token(&logout_input.refresh_token)).await {
+		Ok(Some(session)) => session,
+		Ok(None) => return Err(HttpError::unauthorized("Invalid refresh token")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find session")),
+	};
+
+	if app_state.repositories.session.revoke_family(session.family_cuid).await.is_err() {
+		return Err(HttpError::internal_server_error("Failed to revoke session"));
+	}
+
+	Ok(HttpResponse::NoContent().finish())
+}
+
+#[web::get("/sessions")]
+async fn find_sessions(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let current_session = claims.get_session_family();
+
+	let sessions = match app_state.repositories.session.find_active(claims.get_user_uuid()).await {
+		Ok(sessions) => sessions,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find sessions")),
+	};
+
+	let sessions: Vec<Value> = sessions
+		.into_iter()
+		.map(|session| {
+			json!({
+				"id": session.family_cuid,
+				"user_agent": session.user_agent,
+				"ip_address": session.ip_address,
+				"created_at": session.created_at,
+				"last_seen_at": session.last_seen_at,
+				"expires_at": session.expires_at,
+				"current": session.family_cuid == current_session,
+			})
+		})
+		.collect();
+
+	Ok(HttpResponse::Ok().json(&json!({ "sessions": sessions })))
+}
+
+#[web::delete("/sessions")]
+async fn revoke_other_sessions(state: web::types::State<AppStateType>, claims: Claims) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	let revoked = match app_state.repositories.session.revoke_others(claims.get_user_uuid(), claims.get_session_family()).await {
+		Ok(revoked) => revoked,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to revoke sessions")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "revoked": revoked })))
+}
+
+#[web::delete("/sessions/{id}")]
+async fn revoke_session(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	id: web::types::Path<String>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	match app_state.repositories.session.find_active_one(claims.get_user_uuid(), id.clone()).await {
+		Ok(Some(_)) => {}
+		Ok(None) => return Err(HttpError::not_found("Session not found")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find session")),
+	};
+
+	if app_state.repositories.session.revoke_family(id.clone()).await.is_err() {
+		return Err(HttpError::internal_server_error("Failed to revoke session"));
+	}
+
+	Ok(HttpResponse::NoContent().finish())
+}
+
+#[web::post("/password")]
+async fn change_password(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	password_input: web::types::Json<UserPasswordInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let UserPasswordInput { current_password, new_password } = password_input.into_inner();
+
+	match app_state.repositories.user.verify_password(claims.get_user_uuid(), current_password).await {
+		Ok(true) => {}
+		Ok(false) => return Err(HttpError::forbidden("Current password is incorrect")),
+		Err(_) => return Err(HttpError::internal_server_error("Failed to verify password")),
+	}
+
+	validate_password(&new_password)?;
+
+	if app_state
+		.repositories
+		.user
+		.update_password(claims.get_user_uuid(), new_password, claims.get_session_family())
+		.await
+		.is_err()
+	{
+		return Err(HttpError::internal_server_error("Failed to update password"));
+	}
+
+	Ok(HttpResponse::NoContent().finish())
+}
+
+#[web::post("/password/forgot")]
+async fn forgot_password(
+	state: web::types::State<AppStateType>,
+	forgot_input: web::types::Json<UserForgotPasswordInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	let user = match app_state.repositories.user.find_by_email(forgot_input.email.clone()).await {
+		Ok(user) => user,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to find user")),
+	};
+
+	// The response is the same whether or not the email belongs to an account, so it cannot be used to probe for users.
+	if let Some(user) = user {
+		let token = generate_token();
+		let ttl = std::env::var("PASSWORD_RESET_TTL_MINUTES").ok().and_then(|minutes| minutes.parse().ok());
+		let expires_at = db_now_datetime() + chrono::Duration::minutes(ttl.unwrap_or(DEFAULT_PASSWORD_RESET_TTL_MINUTES));
+
+		if app_state.repositories.password_reset.create(user.uuid, hash_token(&token), expires_at).await.is_err() {
+			return Err(HttpError::internal_server_error("Failed to create password reset"));
+		}
+
+		// Sending happens in the background so that the response time does not reveal whether a mail was sent either.
+		let mailer = app_state.mailer.clone();
+		let mail = password_reset_mail(user.email, &token);
+		ntex::rt::spawn(async move {
+			if let Err(e) = mailer.send(mail).await {
+				error!("Failed to send password reset mail: {}", e);
+			}
+		});
+	}
+
+	Ok(HttpResponse::Accepted().json(&json!({ "message": "If the account exists, a password reset email has been sent" })))
+}
+
+#[web::post("/password/reset")]
+async fn reset_password(
+	state: web::types::State<AppStateType>,
+	reset_input: web::types::Json<UserResetPasswordInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+	let UserResetPasswordInput { token, new_password } = reset_input.into_inner();
+
+	validate_password(&new_password)?;
+
+	match app_state.repositories.password_reset.redeem(hash_token(&token), new_password).await {
+		Ok(Some(_)) => Ok(HttpResponse::NoContent().finish()),
+		Ok(None) => Err(HttpError::bad_request("Invalid or expired reset token")),
+		Err(_) => Err(HttpError::internal_server_error("Failed to reset password")),
+	}
 }
 
 #[web::get("/info")]
@@ -114,9 +466,43 @@ async fn get_user(state: web::types::State<AppStateType>, claims: Claims) -> Res
 		Err(_) => return Err(HttpError::internal_server_error("Failed to get user")),
 	};
 
-	Ok(HttpResponse::Ok().json(&json!({ "name": user.name, "email": user.email })))
+	Ok(HttpResponse::Ok().json(&json!({ "name": user.name, "email": user.email, "timezone": user.timezone })))
+}
+
+#[web::put("/timezone")]
+async fn update_timezone(
+	state: web::types::State<AppStateType>,
+	claims: Claims,
+	user_input: web::types::Json<UserTimezoneInput>,
+) -> Result<HttpResponse, HttpError> {
+	let app_state = state.read().await;
+
+	if parse_timezone(&user_input.timezone).is_none() {
+		return Err(HttpError::bad_request("Invalid timezone"));
+	}
+
+	let user = match app_state.repositories.user.update_timezone(claims.get_user_uuid(), user_input.timezone.clone()).await {
+		Ok(user) => user,
+		Err(_) => return Err(HttpError::internal_server_error("Failed to update timezone")),
+	};
+
+	Ok(HttpResponse::Ok().json(&json!({ "name": user.name, "email": user.email, "timezone": user.timezone })))
 }
 
 pub fn init(config: &mut web::ServiceConfig) {
-	config.service(web::scope("/user").service(create_user).service(login_user).service(get_user));
+	config.service(
+		web::scope("/user")
+			.service(create_user)
+			.service(login_user)
+			.service(refresh_session)
+			.service(logout_user)
+			.service(find_sessions)
+			.service(revoke_other_sessions)
+			.service(revoke_session)
+			.service(change_password)
+			.service(forgot_password)
+			.service(reset_password)
+			.service(get_user)
+			.service(update_timezone),
+	);
 }
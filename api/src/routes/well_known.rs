@@ -0,0 +1,16 @@
+use crate::states::app::AppStateType;
+use ntex::{
+	http,
+	web::{self, HttpResponse},
+};
+
+#[web::get("/jwks.json")]
+async fn jwks(state: web::types::State<AppStateType>) -> HttpResponse {
+	let app_state = state.read().await;
+
+	HttpResponse::Ok().header(http::header::CACHE_CONTROL, "public, max-age=300").json(app_state.keys.jwks())
+}
+
+pub fn init(config: &mut web::ServiceConfig) {
+	config.service(web::scope("/.well-known").service(jwks));
+}
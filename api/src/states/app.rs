@@ -1,4 +1,4 @@
-use crate::{db, repositories};
+use crate::{db, mailer::Mailer, repositories, storage::Storage, utils::jwt::KeyRing};
 use db::*;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -6,12 +6,20 @@ use tokio::sync::RwLock;
 pub struct AppState {
 	pub db: Arc<PrismaClient>,
 	pub repositories: repositories::Repositories,
-	pub secret: &'static str,
+	pub storage: Arc<dyn Storage>,
+	pub mailer: Arc<dyn Mailer>,
+	pub keys: KeyRing,
 }
 
 impl AppState {
-	pub fn new(db: Arc<PrismaClient>, repositories: repositories::Repositories) -> Self {
-		Self { db, repositories, secret: option_env!("JWT_SECRET").unwrap_or("secret") }
+	pub fn new(
+		db: Arc<PrismaClient>,
+		repositories: repositories::Repositories,
+		storage: Arc<dyn Storage>,
+		mailer: Arc<dyn Mailer>,
+		keys: KeyRing,
+	) -> Self {
+		Self { db, repositories, storage, mailer, keys }
 	}
 }
 
@@ -0,0 +1,97 @@
+use super::Storage;
+use async_trait::async_trait;
+use std::{
+	io::{self, SeekFrom},
+	ops::Range,
+	path::PathBuf,
+};
+use tokio::{
+	fs,
+	io::{AsyncReadExt, AsyncSeekExt},
+};
+
+pub struct LocalStorage {
+	root: PathBuf,
+}
+
+impl LocalStorage {
+	pub fn new(root: impl Into<PathBuf>) -> Self {
+		Self { root: root.into() }
+	}
+
+	fn path(&self, key: &str) -> io::Result<PathBuf> {
+		if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Invalid storage key"));
+		}
+
+		Ok(self.root.join(key))
+	}
+}
+
+#[async_trait]
+impl Storage for LocalStorage {
+	async fn put(&self, key: &str, data: Vec<u8>) -> io::Result<()> {
+		let path = self.path(key)?;
+		fs::create_dir_all(&self.root).await?;
+		fs::write(path, data).await
+	}
+
+	async fn get(&self, key: &str, range: Range<u64>) -> io::Result<Vec<u8>> {
+		let mut file = fs::File::open(self.path(key)?).await?;
+		file.seek(SeekFrom::Start(range.start)).await?;
+
+		let mut data = vec![0; (range.end - range.start) as usize];
+		file.read_exact(&mut data).await?;
+
+		Ok(data)
+	}
+
+	async fn delete(&self, key: &str) -> io::Result<()> {
+		match fs::remove_file(self.path(key)?).await {
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+			result => result,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn temp_storage(name: &str) -> LocalStorage {
+		LocalStorage::new(std::env::temp_dir().join(format!("local-storage-{}-{}", name, std::process::id())))
+	}
+
+	#[tokio::test]
+	async fn reads_back_the_requested_range() {
+		let storage = temp_storage("range");
+		storage.put("file", b"hello world".to_vec()).await.unwrap();
+
+		assert_eq!(storage.get("file", 0..11).await.unwrap(), b"hello world");
+		assert_eq!(storage.get("file", 6..11).await.unwrap(), b"world");
+		assert!(storage.get("file", 6..20).await.is_err());
+
+		fs::remove_dir_all(&storage.root).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn deleting_a_missing_file_succeeds() {
+		let storage = temp_storage("delete");
+		storage.put("file", b"data".to_vec()).await.unwrap();
+
+		storage.delete("file").await.unwrap();
+		storage.delete("file").await.unwrap();
+		assert!(storage.get("file", 0..4).await.is_err());
+
+		fs::remove_dir_all(&storage.root).await.unwrap();
+	}
+
+	#[tokio::test]
+	async fn rejects_keys_that_leave_the_root() {
+		let storage = temp_storage("keys");
+
+		for key in ["", "../file", "dir/file", "file.txt"] {
+			assert_eq!(storage.put(key, vec![]).await.unwrap_err().kind(), io::ErrorKind::InvalidInput);
+		}
+	}
+}
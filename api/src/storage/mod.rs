@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use std::{io, ops::Range, sync::Arc};
+
+pub mod local;
+
+const DEFAULT_DIR: &str = "./attachments";
+
+#[async_trait]
+pub trait Storage: Send + Sync {
+	async fn put(&self, key: &str, data: Vec<u8>) -> io::Result<()>;
+
+	async fn get(&self, key: &str, range: Range<u64>) -> io::Result<Vec<u8>>;
+
+	async fn delete(&self, key: &str) -> io::Result<()>;
+}
+
+pub fn from_env() -> Arc<dyn Storage> {
+	let dir = std::env::var("ATTACHMENT_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+
+	Arc::new(local::LocalStorage::new(dir))
+}
+
+pub async fn delete_all(storage: &dyn Storage, keys: Vec<String>) {
+	for key in keys {
+		if let Err(e) = storage.delete(&key).await {
+			error!("Failed to delete stored file {}: {}", key, e);
+		}
+	}
+}
@@ -1,5 +1,25 @@
-use chrono::{DateTime, FixedOffset, Utc};
+use chrono::{DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use chrono_tz::Tz;
 
 pub fn db_now_datetime() -> DateTime<FixedOffset> {
 	Utc::now().with_timezone(&FixedOffset::east_opt(3 * 3600).unwrap())
 }
+
+pub fn parse_timezone(name: &str) -> Option<Tz> {
+	name.parse::<Tz>().ok()
+}
+
+pub fn start_of_day(date: NaiveDate, tz: Tz) -> DateTime<FixedOffset> {
+	local_datetime(date.and_time(NaiveTime::MIN), tz)
+}
+
+pub fn local_datetime(local: NaiveDateTime, tz: Tz) -> DateTime<FixedOffset> {
+	match tz.from_local_datetime(&local) {
+		LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => datetime.fixed_offset(),
+		// The time falls in a DST gap, so use the first local time after the clocks jump forward.
+		LocalResult::None => (1..=24 * 60)
+			.find_map(|minutes| tz.from_local_datetime(&(local + Duration::minutes(minutes))).earliest())
+			.unwrap_or_else(|| tz.from_utc_datetime(&local))
+			.fixed_offset(),
+	}
+}
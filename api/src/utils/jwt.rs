@@ -0,0 +1,171 @@
+use jsonwebtoken::{
+	decode, decode_header, encode,
+	errors::{Error, ErrorKind},
+	jwk::{Jwk, JwkSet},
+	Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::HashMap, io, path::Path, str::FromStr};
+
+use super::token::hash_token;
+
+const DEFAULT_KEY_ID: &str = "default";
+const DEVELOPMENT_SECRET: &str = "secret";
+
+struct VerificationKey {
+	algorithm: Algorithm,
+	key: DecodingKey,
+}
+
+pub struct KeyRing {
+	kid: String,
+	algorithm: Algorithm,
+	signing_key: EncodingKey,
+	verification_keys: HashMap<String, VerificationKey>,
+	jwks: JwkSet,
+}
+
+fn invalid_data(message: String) -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn key_id(path: &str) -> String {
+	Path::new(path).file_stem().and_then(|stem| stem.to_str()).unwrap_or(DEFAULT_KEY_ID).to_string()
+}
+
+// A lone secret keeps the id its tokens have always carried, but once JWT_PREVIOUS_SECRET takes over that id the new
+// secret is told apart by a digest of itself.
+fn secret_key_id(secret: &str) -> String {
+	match std::env::var("JWT_PREVIOUS_SECRET") {
+		Ok(_) => format!("hs-{}", &hash_token(secret)[..16]),
+		Err(_) => DEFAULT_KEY_ID.to_string(),
+	}
+}
+
+fn parse_algorithm(algorithm: &str) -> io::Result<Algorithm> {
+	match Algorithm::from_str(algorithm) {
+		Ok(algorithm @ (Algorithm::HS256 | Algorithm::RS256 | Algorithm::EdDSA)) => Ok(algorithm),
+		_ => Err(invalid_data(format!("Unsupported JWT algorithm {}", algorithm))),
+	}
+}
+
+// Returns the signing key together with the key used to verify its tokens, and the public JWK for asymmetric keys.
+fn load_key(algorithm: Algorithm, material: &[u8]) -> io::Result<(EncodingKey, DecodingKey, Option<Jwk>)> {
+	let encoding_key = match algorithm {
+		Algorithm::HS256 => return Ok((EncodingKey::from_secret(material), DecodingKey::from_secret(material), None)),
+		Algorithm::RS256 => EncodingKey::from_rsa_pem(material),
+		_ => EncodingKey::from_ed_pem(material),
+	}
+	.map_err(|e| invalid_data(format!("Invalid {:?} private key: {}", algorithm, e)))?;
+
+	let jwk = Jwk::from_encoding_key(&encoding_key, algorithm).map_err(|e| invalid_data(e.to_string()))?;
+	let decoding_key = DecodingKey::from_jwk(&jwk).map_err(|e| invalid_data(e.to_string()))?;
+
+	Ok((encoding_key, decoding_key, Some(jwk)))
+}
+
+fn read_key_file(path: &str, algorithm: Algorithm) -> io::Result<Vec<u8>> {
+	let material = std::fs::read(path)?;
+
+	match algorithm {
+		Algorithm::HS256 => Ok(String::from_utf8_lossy(&material).trim().as_bytes().to_vec()),
+		_ => Ok(material),
+	}
+}
+
+impl KeyRing {
+	// Reads the signing key from JWT_SECRET or JWT_KEY_FILE, plus the previous keys that are still accepted for
+	// verification while tokens signed with them expire: JWT_PREVIOUS_SECRET (with JWT_PREVIOUS_KEY_ID) and the
+	// `[kid=ID:][ALG:]path` entries of JWT_PREVIOUS_KEY_FILES.
+	pub fn from_env() -> io::Result<Self> {
+		let algorithm = parse_algorithm(&std::env::var("JWT_ALGORITHM").unwrap_or_else(|_| "HS256".to_string()))?;
+
+		let (material, default_kid) = match (std::env::var("JWT_KEY_FILE"), std::env::var("JWT_SECRET")) {
+			(Ok(path), _) => (read_key_file(&path, algorithm)?, key_id(&path)),
+			(Err(_), Ok(secret)) if algorithm == Algorithm::HS256 => {
+				let kid = secret_key_id(&secret);
+				(secret.into_bytes(), kid)
+			}
+			_ if cfg!(debug_assertions) && algorithm == Algorithm::HS256 => {
+				warn!("No JWT key configured, using an insecure development secret");
+				(DEVELOPMENT_SECRET.as_bytes().to_vec(), DEFAULT_KEY_ID.to_string())
+			}
+			_ => return Err(invalid_data("JWT_SECRET or JWT_KEY_FILE must be set".to_string())),
+		};
+
+		if material.is_empty() {
+			return Err(invalid_data("The JWT key must not be empty".to_string()));
+		}
+
+		let kid = std::env::var("JWT_KEY_ID").unwrap_or(default_kid);
+		let (signing_key, decoding_key, jwk) = load_key(algorithm, &material)?;
+
+		let mut key_ring =
+			Self { kid: kid.clone(), algorithm, signing_key, verification_keys: HashMap::new(), jwks: JwkSet { keys: vec![] } };
+		key_ring.add_verification_key(kid, algorithm, decoding_key, jwk)?;
+
+		if let Ok(secret) = std::env::var("JWT_PREVIOUS_SECRET") {
+			if secret.is_empty() {
+				return Err(invalid_data("The previous JWT secret must not be empty".to_string()));
+			}
+
+			let kid = std::env::var("JWT_PREVIOUS_KEY_ID").unwrap_or_else(|_| DEFAULT_KEY_ID.to_string());
+			let (_, decoding_key, jwk) = load_key(Algorithm::HS256, secret.as_bytes())?;
+			key_ring.add_verification_key(kid, Algorithm::HS256, decoding_key, jwk)?;
+		}
+
+		let previous = std::env::var("JWT_PREVIOUS_KEY_FILES").unwrap_or_default();
+		for entry in previous.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+			let (kid, entry) = match entry.strip_prefix("kid=").and_then(|entry| entry.split_once(':')) {
+				Some((kid, entry)) => (Some(kid.to_string()), entry),
+				None => (None, entry),
+			};
+			let (algorithm, path) = match entry.split_once(':') {
+				Some((algorithm, path)) => (parse_algorithm(algorithm)?, path),
+				None => (algorithm, entry),
+			};
+
+			let (_, decoding_key, jwk) = load_key(algorithm, &read_key_file(path, algorithm)?)?;
+			key_ring.add_verification_key(kid.unwrap_or_else(|| key_id(path)), algorithm, decoding_key, jwk)?;
+		}
+
+		Ok(key_ring)
+	}
+
+	fn add_verification_key(&mut self, kid: String, algorithm: Algorithm, key: DecodingKey, jwk: Option<Jwk>) -> io::Result<()> {
+		if self.verification_keys.contains_key(&kid) {
+			return Err(invalid_data(format!("Duplicate JWT key id {}", kid)));
+		}
+
+		if let Some(mut jwk) = jwk {
+			jwk.common.key_id = Some(kid.clone());
+			self.jwks.keys.push(jwk);
+		}
+
+		self.verification_keys.insert(kid, VerificationKey { algorithm, key });
+
+		Ok(())
+	}
+
+	pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String, Error> {
+		let mut header = Header::new(self.algorithm);
+		header.kid = Some(self.kid.clone());
+
+		encode(&header, claims, &self.signing_key)
+	}
+
+	pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<TokenData<T>, Error> {
+		let header = decode_header(token)?;
+		let kid = header.kid.unwrap_or_else(|| self.kid.clone());
+
+		let Some(verification_key) = self.verification_keys.get(&kid) else {
+			return Err(ErrorKind::InvalidToken.into());
+		};
+
+		decode(token, &verification_key.key, &Validation::new(verification_key.algorithm))
+	}
+
+	pub fn jwks(&self) -> &JwkSet {
+		&self.jwks
+	}
+}
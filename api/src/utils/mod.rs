@@ -1 +1,7 @@
+pub mod cursor;
 pub mod datetime;
+pub mod jwt;
+pub mod patch;
+pub mod rank;
+pub mod recurrence;
+pub mod token;
@@ -0,0 +1,90 @@
+const LOW: u8 = b'a' - 1;
+const HIGH: u8 = b'z' + 1;
+const RADIX: u64 = 26;
+const MAX_RANK_LEN: usize = 16;
+
+fn digit(rank: &[u8], index: usize, fallback: u8) -> u8 {
+	rank.get(index).copied().unwrap_or(fallback)
+}
+
+fn rank_after(prev: &[u8]) -> Vec<u8> {
+	match prev.iter().position(|&c| c < b'z') {
+		Some(index) => [&prev[..index], &[prev[index] + 1]].concat(),
+		None => [prev, b"n"].concat(),
+	}
+}
+
+// Ranks only use lowercase letters so Postgres collations order them like bytes, and never end in 'a' so there is
+// always room before them.
+fn midpoint(prev: &[u8], next: &[u8]) -> Vec<u8> {
+	let mut index = 0;
+	let (mut p, mut n) = (digit(prev, 0, LOW), digit(next, 0, HIGH));
+	while p == n {
+		index += 1;
+		p = digit(prev, index, LOW);
+		n = digit(next, index, HIGH);
+	}
+
+	let mut rank = prev[..index].to_vec();
+	index += 1;
+
+	if p == LOW {
+		while n == b'a' {
+			rank.push(b'a');
+			n = digit(next, index, HIGH);
+			index += 1;
+		}
+		if n == b'b' {
+			rank.push(b'a');
+			n = HIGH;
+		}
+	} else if p + 1 == n {
+		rank.push(p);
+		n = HIGH;
+		loop {
+			p = digit(prev, index, LOW);
+			index += 1;
+			if p != b'z' {
+				break;
+			}
+			rank.push(b'z');
+		}
+	}
+
+	rank.push(((p as u16 + n as u16 + 1) / 2) as u8);
+	rank
+}
+
+pub fn rank_between(prev: Option<&str>, next: Option<&str>) -> Option<String> {
+	let rank = match (prev, next) {
+		(Some(prev), Some(next)) if prev >= next => return None,
+		(Some(prev), None) => rank_after(prev.as_bytes()),
+		(prev, next) => midpoint(prev.unwrap_or_default().as_bytes(), next.unwrap_or_default().as_bytes()),
+	};
+
+	String::from_utf8(rank).ok().filter(|rank| rank.len() <= MAX_RANK_LEN)
+}
+
+pub fn spread_ranks(count: usize) -> Vec<String> {
+	let slots = count as u64 + 1;
+	let mut width = 1;
+	while RADIX.pow(width) < slots * RADIX {
+		width += 1;
+	}
+	let step = RADIX.pow(width) / slots;
+
+	(1..slots)
+		.map(|slot| {
+			let mut value = step * slot;
+			let mut rank = vec![b'a'; width as usize];
+			for c in rank.iter_mut().rev() {
+				*c = b'a' + (value % RADIX) as u8;
+				value /= RADIX;
+			}
+			if rank.last() == Some(&b'a') {
+				rank.push(b'n');
+			}
+			String::from_utf8(rank).unwrap_or_default()
+		})
+		.collect()
+}
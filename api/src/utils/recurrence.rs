@@ -0,0 +1,29 @@
+use crate::utils::datetime::local_datetime;
+use chrono::{DateTime, FixedOffset, TimeZone};
+use rrule::{RRule, Tz, Unvalidated};
+
+fn parse_rrule(rule: &str) -> Option<RRule<Unvalidated>> {
+	let rule = rule.trim();
+	let rule = rule.strip_prefix("RRULE:").unwrap_or(rule);
+
+	// Each occurrence is generated from the previous due date, so COUNT would restart every time; use UNTIL instead.
+	if rule.to_uppercase().contains("COUNT=") {
+		return None;
+	}
+
+	rule.parse().ok()
+}
+
+pub fn is_valid_rrule(rule: &str, dtstart: DateTime<FixedOffset>) -> bool {
+	parse_rrule(rule).is_some_and(|rrule| rrule.build(dtstart.with_timezone(&Tz::UTC)).is_ok())
+}
+
+// Expands the rule on the wall clock of `tz`, so a task due at 09:00 stays at 09:00 across DST changes.
+pub fn next_occurrence(rule: &str, after: DateTime<FixedOffset>, tz: chrono_tz::Tz) -> Option<DateTime<FixedOffset>> {
+	let start = Tz::UTC.from_utc_datetime(&after.with_timezone(&tz).naive_local());
+	let rrule_set = parse_rrule(rule)?.build(start).ok()?;
+
+	let next = rrule_set.after(start).all(2).dates.into_iter().find(|date| *date > start)?;
+
+	Some(local_datetime(next.naive_utc(), tz))
+}
@@ -0,0 +1,17 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+const TOKEN_BYTES: usize = 32;
+
+pub fn generate_token() -> String {
+	let mut bytes = [0u8; TOKEN_BYTES];
+	rand::thread_rng().fill_bytes(&mut bytes);
+
+	URL_SAFE_NO_PAD.encode(bytes)
+}
+
+// Tokens are random enough that a fast unsalted hash is sufficient, and it keeps them searchable by hash.
+pub fn hash_token(token: &str) -> String {
+	Sha256::digest(token.as_bytes()).iter().map(|byte| format!("{:02x}", byte)).collect()
+}